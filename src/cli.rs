@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+pub const USAGE: &str = "Usage:
+  moquist serve <config> [--host <host>] [--port <port>] [--scale <scale>] [--seed <seed>] [--watch]
+  moquist validate <config>
+  moquist generate <config> <route> [--method <method>] [--id <id>] [--scale <scale>] [--seed <seed>]
+  moquist <config> [scale] [seed]
+
+Commands:
+  serve     Serves the mocked API. With `--watch` it reloads the configuration whenever the file changes.
+  validate  Checks the configuration and exits with a non-zero status if it has errors.
+  generate  Prints the response of a route, e.g. `/people` or `/people/3`, without starting the server.
+
+The MOQUIST_HOST and MOQUIST_PORT environment variables set the host and port when their flags aren't given.
+Port 0 picks any free port, which is printed on startup.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServeOptions {
+	pub host: String,
+	pub port: u16,
+	pub scale: usize,
+	pub seed: usize,
+	/// Whether to reload the configuration when the file changes.
+	pub watch: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenerateOptions {
+	pub route: String,
+	pub method: String,
+	/// Overrides the value of the route's last parameter.
+	pub id: Option<String>,
+	pub scale: usize,
+	pub seed: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Commands {
+	Serve(String, ServeOptions),
+	Validate(String),
+	Generate(String, GenerateOptions),
+	Help,
+}
+
+const DEFAULT_HOST: &str = "0.0.0.0";
+const DEFAULT_PORT: u16 = 80;
+const DEFAULT_SCALE: usize = 16;
+const DEFAULT_SEED: usize = 0;
+
+/// Flags that take no value.
+const SWITCHES: [&str; 1] = ["watch"];
+
+type Flags = Vec<(String, String)>;
+
+/// Splits the arguments into positional ones and `--flag value` (or `--flag=value`) pairs.
+/// Switches, like `--watch`, get the value `true`.
+fn split_args(args: &[String]) -> Result<(Vec<String>, Flags), String> {
+	let mut positional = Vec::new();
+	let mut flags = Vec::new();
+
+	let mut args = args.iter();
+
+	while let Some(arg) = args.next() {
+		if let Some(flag) = arg.strip_prefix("--") {
+			match flag.split_once('=') {
+				Some((flag, value)) => flags.push((flag.to_string(), value.to_string())),
+				None if SWITCHES.contains(&flag) => flags.push((flag.to_string(), "true".to_string())),
+				None => {
+					let value = args.next().ok_or_else(|| format!("missing value for `--{}`", flag))?;
+					flags.push((flag.to_string(), value.to_string()));
+				}
+			}
+		} else {
+			positional.push(arg.to_string());
+		}
+	}
+
+	Ok((positional, flags))
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+	value.parse::<T>().map_err(|_| format!("invalid {} `{}`", name, value))
+}
+
+/// Parses the command line arguments, without the program name, falling back to the environment variables in `env`.
+pub fn parse_args(args: &[String], env: &HashMap<String, String>) -> Result<Commands, String> {
+	if args.is_empty() || args.iter().any(|arg| arg == "-h" || arg == "--help") {
+		return Ok(Commands::Help);
+	}
+
+	let (command, rest) = match args[0].as_str() {
+		"serve" | "validate" | "generate" => (args[0].as_str(), &args[1..]),
+		// Without a command, the arguments are the configuration path, scale and seed
+		_ => ("serve", args),
+	};
+
+	let (positional, flags) = split_args(rest)?;
+
+	let mut positional = positional.into_iter();
+
+	let path = positional.next().ok_or("missing the configuration file path")?;
+
+	let flag = |name: &str| flags.iter().rev().find(|(flag, _)| flag == name).map(|(_, value)| value.as_str());
+
+	let allowed: &[&str] = match command {
+		"serve" => &["host", "port", "scale", "seed", "watch"],
+		"validate" => &[],
+		_ => &["method", "id", "scale", "seed"],
+	};
+
+	if let Some((unknown, _)) = flags.iter().find(|(flag, _)| !allowed.contains(&flag.as_str())) {
+		return Err(format!("unknown option `--{}` for `{}`", unknown, command));
+	}
+
+	match command {
+		"serve" => {
+			let scale = positional.next();
+			let seed = positional.next();
+
+			if let Some(extra) = positional.next() {
+				return Err(format!("unexpected argument `{}`", extra));
+			}
+
+			let host = flag("host").or(env.get("MOQUIST_HOST").map(|host| host.as_str()));
+			let port = flag("port").or(env.get("MOQUIST_PORT").map(|port| port.as_str()));
+
+			Ok(Commands::Serve(path, ServeOptions {
+				host: host.unwrap_or(DEFAULT_HOST).to_string(),
+				port: port.map(|port| parse_number("port", port)).transpose()?.unwrap_or(DEFAULT_PORT),
+				scale: flag("scale").or(scale.as_deref()).map(|scale| parse_number("scale", scale)).transpose()?.unwrap_or(DEFAULT_SCALE),
+				seed: flag("seed").or(seed.as_deref()).map(|seed| parse_number("seed", seed)).transpose()?.unwrap_or(DEFAULT_SEED),
+				watch: flag("watch").map(|watch| watch.parse::<bool>().map_err(|_| format!("invalid watch `{}`", watch))).transpose()?.unwrap_or(false),
+			}))
+		},
+		"validate" => {
+			if let Some(extra) = positional.next() {
+				return Err(format!("unexpected argument `{}`", extra));
+			}
+
+			Ok(Commands::Validate(path))
+		},
+		_ => {
+			let route = positional.next().ok_or("missing the route to generate")?;
+
+			if let Some(extra) = positional.next() {
+				return Err(format!("unexpected argument `{}`", extra));
+			}
+
+			Ok(Commands::Generate(path, GenerateOptions {
+				route,
+				method: flag("method").unwrap_or("get").to_string(),
+				id: flag("id").map(|id| id.to_string()),
+				scale: flag("scale").map(|scale| parse_number("scale", scale)).transpose()?.unwrap_or(DEFAULT_SCALE),
+				seed: flag("seed").map(|seed| parse_number("seed", seed)).transpose()?.unwrap_or(DEFAULT_SEED),
+			}))
+		},
+	}
+}
+
+/// Matches a request path, like `/people/3`, against a route, like `/people/:id`.
+/// Returns the route parameters if it matches.
+pub fn match_route(route: &str, path: &str) -> Option<Vec<(String, String)>> {
+	let route_segments = route.split('/').collect::<Vec<_>>();
+	let path_segments = path.split('/').collect::<Vec<_>>();
+
+	if route_segments.len() != path_segments.len() {
+		return None;
+	}
+
+	let mut params = Vec::new();
+
+	for (route_segment, path_segment) in route_segments.iter().zip(path_segments) {
+		match route_segment.strip_prefix(':') {
+			Some(name) => params.push((name.to_string(), path_segment.to_string())),
+			None if *route_segment == path_segment => {},
+			None => return None,
+		}
+	}
+
+	Some(params)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn args(args: &[&str]) -> Vec<String> {
+		args.iter().map(|arg| arg.to_string()).collect()
+	}
+
+	fn parse_args(args: &[String]) -> Result<Commands, String> {
+		super::parse_args(args, &HashMap::new())
+	}
+
+	#[test]
+	fn test_parse_args() {
+		assert_eq!(parse_args(&args(&["mocks.json5"])), Ok(Commands::Serve("mocks.json5".to_string(), ServeOptions { host: "0.0.0.0".to_string(), port: 80, scale: 16, seed: 0, watch: false })));
+		assert_eq!(parse_args(&args(&["mocks.json5", "32", "7"])), Ok(Commands::Serve("mocks.json5".to_string(), ServeOptions { host: "0.0.0.0".to_string(), port: 80, scale: 32, seed: 7, watch: false })));
+		assert_eq!(parse_args(&args(&["serve", "mocks.json5", "--port", "8080", "--host=127.0.0.1", "--seed", "3"])), Ok(Commands::Serve("mocks.json5".to_string(), ServeOptions { host: "127.0.0.1".to_string(), port: 8080, scale: 16, seed: 3, watch: false })));
+		assert_eq!(parse_args(&args(&["serve", "mocks.json5", "--watch", "--port", "8080"])), Ok(Commands::Serve("mocks.json5".to_string(), ServeOptions { host: "0.0.0.0".to_string(), port: 8080, scale: 16, seed: 0, watch: true })));
+		assert_eq!(parse_args(&args(&["validate", "mocks.json5"])), Ok(Commands::Validate("mocks.json5".to_string())));
+		assert_eq!(parse_args(&args(&["generate", "mocks.json5", "/people/:id", "--id", "3"])), Ok(Commands::Generate("mocks.json5".to_string(), GenerateOptions { route: "/people/:id".to_string(), method: "get".to_string(), id: Some("3".to_string()), scale: 16, seed: 0 })));
+		assert_eq!(parse_args(&args(&["--help"])), Ok(Commands::Help));
+
+		assert!(parse_args(&args(&["serve"])).is_err());
+		assert!(parse_args(&args(&["serve", "mocks.json5", "--port", "http"])).is_err());
+		assert!(parse_args(&args(&["serve", "mocks.json5", "--port"])).is_err());
+		assert!(parse_args(&args(&["validate", "mocks.json5", "--seed", "3"])).is_err());
+		assert!(parse_args(&args(&["generate", "mocks.json5"])).is_err());
+	}
+
+	#[test]
+	fn test_parse_args_env() {
+		let env = HashMap::from([("MOQUIST_HOST".to_string(), "127.0.0.1".to_string()), ("MOQUIST_PORT".to_string(), "0".to_string())]);
+
+		assert_eq!(super::parse_args(&args(&["serve", "mocks.json5"]), &env), Ok(Commands::Serve("mocks.json5".to_string(), ServeOptions { host: "127.0.0.1".to_string(), port: 0, scale: 16, seed: 0, watch: false })));
+		assert_eq!(super::parse_args(&args(&["serve", "mocks.json5", "--port", "8080"]), &env), Ok(Commands::Serve("mocks.json5".to_string(), ServeOptions { host: "127.0.0.1".to_string(), port: 8080, scale: 16, seed: 0, watch: false })));
+
+		let env = HashMap::from([("MOQUIST_PORT".to_string(), "http".to_string())]);
+		assert!(super::parse_args(&args(&["serve", "mocks.json5"]), &env).is_err());
+	}
+
+	#[test]
+	fn test_match_route() {
+		assert_eq!(match_route("/people", "/people"), Some(vec![]));
+		assert_eq!(match_route("/people/:id", "/people/3"), Some(vec![("id".to_string(), "3".to_string())]));
+		assert_eq!(match_route("/orgs/:orgId/people/:personId", "/orgs/acme/people/3"), Some(vec![("orgId".to_string(), "acme".to_string()), ("personId".to_string(), "3".to_string())]));
+		assert_eq!(match_route("/people/:id", "/people"), None);
+		assert_eq!(match_route("/people/:id", "/orgs/3"), None);
+	}
+}
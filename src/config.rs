@@ -0,0 +1,345 @@
+use std::{collections::{HashMap, HashSet}, path::{Path, PathBuf}};
+
+use crate::validate::{format_path, locate, parse_source, ConfigError};
+
+/// A file of the configuration and its contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct File {
+	pub path: String,
+	pub text: String,
+}
+
+/// A configuration read from a file, or a directory, along with the files it imports, merged into a single source.
+#[derive(Debug, Clone)]
+pub struct Config {
+	/// The files that were parsed, in the order they were read.
+	pub files: Vec<File>,
+	/// Every file that was read, including the ones that couldn't be parsed.
+	pub paths: Vec<String>,
+	pub source: serde_json::Value,
+	/// Errors found reading, parsing or merging the files.
+	pub errors: Vec<ConfigError>,
+}
+
+const EXTENSIONS: [&str; 2] = ["json5", "json"];
+
+/// Lists the configuration files in a directory, sorted by name.
+fn directory_files(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+	let mut files = std::fs::read_dir(path)?
+		.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+		.filter(|path| path.is_file() && path.extension().and_then(|extension| extension.to_str()).is_some_and(|extension| EXTENSIONS.contains(&extension)))
+		.collect::<Vec<_>>();
+
+	files.sort();
+
+	Ok(files)
+}
+
+fn error(file: &File, path: &[&str], message: impl Into<String>) -> ConfigError {
+	ConfigError { path: format_path(path), message: message.into(), file: Some(file.path.clone()), location: locate(&file.text, path) }
+}
+
+fn read_error(path: &Path, error: std::io::Error) -> ConfigError {
+	ConfigError { path: String::new(), message: format!("failed to read {}: {}", path.display(), error), file: None, location: None }
+}
+
+struct Reader {
+	files: Vec<File>,
+	paths: Vec<String>,
+	sources: Vec<serde_json::Value>,
+	errors: Vec<ConfigError>,
+	read: HashSet<PathBuf>,
+}
+
+impl Reader {
+	/// Reads a file, or every file in a directory, and then the files they import.
+	fn read(&mut self, path: &Path) {
+		if path.is_dir() {
+			match directory_files(path) {
+				Ok(files) => files.iter().for_each(|file| self.read(file)),
+				Err(error) => self.errors.push(read_error(path, error)),
+			}
+
+			return;
+		}
+
+		// Files imported more than once, or by each other, are only read the first time
+		if !self.read.insert(path.canonicalize().unwrap_or_else(|_| path.to_path_buf())) {
+			return;
+		}
+
+		self.paths.push(path.display().to_string());
+
+		let text = match std::fs::read_to_string(path) {
+			Ok(text) => text,
+			Err(error) => return self.errors.push(read_error(path, error)),
+		};
+
+		let file = File { path: path.display().to_string(), text };
+
+		let mut source = match parse_source(&file.text) {
+			Ok(source) => source,
+			Err(error) => return self.errors.push(ConfigError { file: Some(file.path), ..error }),
+		};
+
+		// Imports and dictionary files are relative to the file naming them
+		let directory = path.parent().unwrap_or(Path::new("")).to_path_buf();
+
+		self.read_dictionaries(&file, &mut source, &directory);
+
+		let mut imports = Vec::new();
+
+		match source.get("imports") {
+			Some(serde_json::Value::Array(paths)) => {
+				for (i, import) in paths.iter().enumerate() {
+					match import {
+						serde_json::Value::String(import) => imports.push(import.clone()),
+						_ => self.errors.push(error(&file, &["imports", &i.to_string()], "imports must be paths")),
+					}
+				}
+			},
+			Some(_) => self.errors.push(error(&file, &["imports"], "`imports` must be an array")),
+			None => {},
+		}
+
+		self.files.push(file);
+		self.sources.push(source);
+
+		for import in imports {
+			self.read(&directory.join(import));
+		}
+	}
+
+	/// Replaces the dictionaries read from a file, like `{ file: "skus.txt" }`, with the words in it, one per line.
+	fn read_dictionaries(&mut self, file: &File, source: &mut serde_json::Value, directory: &Path) {
+		let Some(serde_json::Value::Object(dictionaries)) = source.get_mut("dictionaries") else {
+			return;
+		};
+
+		for (name, dictionary) in dictionaries.iter_mut() {
+			let Some(serde_json::Value::String(path)) = dictionary.get("file") else {
+				continue;
+			};
+
+			let path = directory.join(path);
+
+			self.paths.push(path.display().to_string());
+
+			match std::fs::read_to_string(&path) {
+				Ok(text) => *dictionary = text.lines().map(str::trim).filter(|line| !line.is_empty()).map(|line| serde_json::Value::String(line.to_string())).collect(),
+				Err(read) => self.errors.push(error(file, &["dictionaries", name, "file"], format!("failed to read {}: {}", path.display(), read))),
+			}
+		}
+	}
+}
+
+type Section = serde_json::Map<String, serde_json::Value>;
+
+struct Merger<'a> {
+	files: &'a [File],
+	errors: Vec<ConfigError>,
+	/// The file that first defined each schema, route or other value, by path.
+	origins: HashMap<Vec<String>, usize>,
+}
+
+impl Merger<'_> {
+	fn conflict(&mut self, path: &[String], index: usize) {
+		let first = self.origins.get(path).map_or("another file", |&origin| self.files[origin].path.as_str());
+		let second = &self.files[index];
+
+		let message = format!("defined in both {} and {}", first, second.path);
+
+		self.errors.push(error(second, &path.iter().map(|segment| segment.as_str()).collect::<Vec<_>>(), message));
+	}
+
+	/// Adds `value` at `path` unless another file already defined it.
+	fn insert(&mut self, target: &mut Section, path: &[String], value: &serde_json::Value, index: usize) {
+		let key = &path[path.len() - 1];
+
+		if target.contains_key(key) {
+			self.conflict(path, index);
+		} else {
+			target.insert(key.clone(), value.clone());
+			self.origins.insert(path.to_vec(), index);
+		}
+	}
+
+	/// Merges route trees. A route can be split across files as long as only one of them defines its response.
+	fn merge_routes(&mut self, target: &mut Section, routes: &Section, path: &[String], index: usize) {
+		for (name, route) in routes {
+			let route_path = [path, std::slice::from_ref(name)].concat();
+
+			let serde_json::Value::Object(route) = route else {
+				self.insert(target, &route_path, route, index);
+				continue;
+			};
+
+			let serde_json::Value::Object(existing) = target.entry(name.clone()).or_insert_with(|| serde_json::Value::Object(Section::new())) else {
+				self.conflict(&route_path, index);
+				continue;
+			};
+
+			if route.keys().any(|key| key != "routes") {
+				if existing.keys().any(|key| key != "routes") {
+					self.conflict(&route_path, index);
+					continue;
+				}
+
+				self.origins.insert(route_path.clone(), index);
+			}
+
+			for (key, value) in route {
+				match (key.as_str(), value) {
+					("routes", serde_json::Value::Object(routes)) => match existing.entry(key.clone()).or_insert_with(|| serde_json::Value::Object(Section::new())) {
+						serde_json::Value::Object(existing) => self.merge_routes(existing, routes, &[route_path.as_slice(), std::slice::from_ref(key)].concat(), index),
+						_ => self.conflict(&route_path, index),
+					},
+					_ => {
+						existing.insert(key.clone(), value.clone());
+					},
+				}
+			}
+		}
+	}
+
+	fn merge(&mut self, sources: &[serde_json::Value]) -> serde_json::Value {
+		let mut merged = Section::new();
+
+		for (index, source) in sources.iter().enumerate() {
+			let serde_json::Value::Object(source) = source else {
+				self.errors.push(error(&self.files[index], &[], "the configuration must be an object"));
+				continue;
+			};
+
+			for (key, value) in source.iter().filter(|(key, _)| *key != "imports") {
+				let path = vec![key.clone()];
+
+				// Sections, like `schemas` or `routes`, are merged entry by entry, anything else must only be defined once
+				let serde_json::Value::Object(entries) = value else {
+					self.insert(&mut merged, &path, value, index);
+					continue;
+				};
+
+				self.origins.entry(path.clone()).or_insert(index);
+
+				let serde_json::Value::Object(section) = merged.entry(key.clone()).or_insert_with(|| serde_json::Value::Object(Section::new())) else {
+					self.conflict(&path, index);
+					continue;
+				};
+
+				if key == "routes" {
+					self.merge_routes(section, entries, &path, index);
+				} else {
+					for (name, entry) in entries {
+						self.insert(section, &[key.clone(), name.clone()], entry, index);
+					}
+				}
+			}
+		}
+
+		serde_json::Value::Object(merged)
+	}
+}
+
+/// Reads the configuration at `path`, a file or a directory of `.json5` and `.json` files, following the `imports` of each file.
+/// Schemas, routes and other sections are merged, and anything defined in two files is reported as an error.
+pub fn read_config(path: &str) -> Config {
+	let mut reader = Reader { files: Vec::new(), paths: Vec::new(), sources: Vec::new(), errors: Vec::new(), read: HashSet::new() };
+
+	reader.read(Path::new(path));
+
+	let Reader { files, paths, sources, mut errors, .. } = reader;
+
+	let mut merger = Merger { files: &files, errors: Vec::new(), origins: HashMap::new() };
+	let source = merger.merge(&sources);
+	errors.extend(merger.errors);
+	errors.sort_by_key(|error| (error.file.as_ref().and_then(|file| paths.iter().position(|path| path == file)), error.location));
+
+	// Only configurations split across files name the file of each error
+	if paths.len() == 1 {
+		for error in &mut errors {
+			error.file = None;
+		}
+	}
+
+	Config { files, paths, source, errors }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Writes `files` to a new directory under the temporary one and returns its path.
+	fn write_files(name: &str, files: &[(&str, &str)]) -> PathBuf {
+		let directory = std::env::temp_dir().join(format!("moquist-{}-{}", name, std::process::id()));
+
+		for (path, text) in files {
+			let path = directory.join(path);
+			std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+			std::fs::write(path, text).unwrap();
+		}
+
+		directory
+	}
+
+	#[test]
+	fn test_read_config_imports() {
+		let directory = write_files("imports", &[
+			("mocks.json5", r#"{ imports: ["schemas/people.json5", "routes.json5"], routes: { "/orgs": { response: "Org[]" } } }"#),
+			("schemas/people.json5", r#"{ schemas: { Person: { fields: { name: { template: "${FULL_NAME}" } } } } }"#),
+			("routes.json5", r#"{ imports: ["mocks.json5"], schemas: { Org: { fields: {} } }, routes: { "/orgs": { routes: { "/:id": { response: "Org" } } }, "/people": { response: "Person[]" } } }"#),
+		]);
+
+		let config = read_config(directory.join("mocks.json5").to_str().unwrap());
+
+		assert!(config.errors.is_empty());
+		assert_eq!(config.files.len(), 3);
+		assert_eq!(config.source["schemas"].as_object().unwrap().keys().collect::<Vec<_>>(), vec!["Org", "Person"]);
+		assert_eq!(config.source["routes"]["/orgs"]["response"], "Org[]");
+		assert_eq!(config.source["routes"]["/orgs"]["routes"]["/:id"]["response"], "Org");
+		assert_eq!(config.source["routes"]["/people"]["response"], "Person[]");
+
+		std::fs::remove_dir_all(directory).unwrap();
+	}
+
+	#[test]
+	fn test_read_config_conflicts() {
+		let directory = write_files("conflicts", &[
+			("a.json5", r#"{ schemas: { Person: { fields: {} } }, routes: { "/people": { response: "Person[]" } } }"#),
+			("b.json5", "{\n\tschemas: {\n\t\tPerson: { fields: {} },\n\t},\n\troutes: {\n\t\t\"/people\": { response: \"Person\" },\n\t},\n}"),
+		]);
+
+		let config = read_config(directory.to_str().unwrap());
+
+		let a = directory.join("a.json5").display().to_string();
+		let b = directory.join("b.json5").display().to_string();
+
+		assert_eq!(config.errors.into_iter().map(|error| error.to_string()).collect::<Vec<_>>(), vec![
+			format!("schemas.Person ({}, line 3, column 3): defined in both {} and {}", b, a, b),
+			format!("routes.\"/people\" ({}, line 6, column 3): defined in both {} and {}", b, a, b),
+		]);
+
+		std::fs::remove_dir_all(directory).unwrap();
+	}
+
+	#[test]
+	fn test_read_config_dictionaries() {
+		let directory = write_files("dictionaries", &[
+			("mocks.json5", r#"{ imports: ["data/dictionaries.json5"] }"#),
+			("data/dictionaries.json5", r#"{ dictionaries: { branches: ["Palermo"], skus: { file: "skus.txt" }, codes: { file: "codes.txt" } } }"#),
+			("data/skus.txt", "SKU-001\n\n  SKU-002  \n"),
+		]);
+
+		let config = read_config(directory.join("mocks.json5").to_str().unwrap());
+
+		assert_eq!(config.source["dictionaries"]["branches"], serde_json::json!(["Palermo"]));
+		assert_eq!(config.source["dictionaries"]["skus"], serde_json::json!(["SKU-001", "SKU-002"]));
+		assert!(config.paths.contains(&directory.join("data/skus.txt").display().to_string()));
+
+		assert_eq!(config.errors.len(), 1);
+		assert_eq!(config.errors[0].path, "dictionaries.codes.file");
+		assert!(config.errors[0].message.starts_with(&format!("failed to read {}", directory.join("data/codes.txt").display())));
+
+		std::fs::remove_dir_all(directory).unwrap();
+	}
+}
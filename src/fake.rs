@@ -1,131 +1,281 @@
-struct FakeField {
-	name: &'static str,
-	values: [&'static str; 12],
+use std::sync::OnceLock;
+
+use crate::random;
+
+/// A list of values embedded at compile time, one per line.
+#[derive(Debug)]
+struct List {
+	text: &'static str,
+	entries: OnceLock<Vec<&'static str>>,
 }
 
-const FIELDS: [FakeField; 12] = [
-	FakeField { name: "first_name", values: FAKE_FIRST_NAMES },
-	FakeField { name: "last_name", values: FAKE_LAST_NAMES },
-	FakeField { name: "email", values: ["a@email.com", "b@email.com", "c@email.com", "d@email.com", "e@email.com", "f@email.com", "g@email.com", "h@email.com", "i@email.com", "j@email.com", "k@email.com", "l@email.com",], },
-	FakeField { name: "phone", values: ["1234567890", "2345678901", "3456789012", "4567890123", "5678901234", "6789012345", "7890123456", "8901234567", "9012345678", "0123456789", "1234567890", "2345678901"] },
-	FakeField { name: "address", values: ["123 Fake St", "456 Fake St", "789 Fake St", "012 Fake St", "345 Fake St", "678 Fake St", "901 Fake St", "234 Fake St", "567 Fake St", "890 Fake St", "123 Fake St", "456 Fake St"] },
-	FakeField { name: "city", values: ["New York", "Los Angeles", "Chicago", "Houston", "Phoenix", "Philadelphia", "San Antonio", "San Diego", "Dallas", "San Jose", "Austin", "Jacksonville"] },
-	FakeField { name: "state", values: ["NY", "CA", "IL", "TX", "AZ", "PA", "TX", "CA", "TX", "CA", "TX", "FL"] },
-	FakeField { name: "zip", values: ["12345", "23456", "34567", "45678", "56789", "67890", "78901", "89012", "90123", "01234", "12345", "23456"] },
-	FakeField { name: "country", values: ["USA", "USA", "USA", "USA", "USA", "USA", "USA", "USA", "USA", "USA", "USA", "USA"] },
-	FakeField { name: "company", values: ["Apple", "Google", "Microsoft", "Amazon", "Facebook", "Twitter", "Uber", "Lyft", "Airbnb", "Netflix", "Spotify", "Slack"] },
-	FakeField { name: "job", values: ["Software Engineer", "Product Manager", "Designer", "Data Scientist", "Sales", "Marketing", "Customer Support", "HR", "Finance", "Operations", "Legal", "Security"] },
-	FakeField { name: "age", values: ["20", "21", "22", "23", "24", "25", "26", "27", "28", "29", "30", "31"] },
-];
+impl List {
+	const fn new(text: &'static str) -> List {
+		List { text, entries: OnceLock::new() }
+	}
 
-const FAKE_FIRST_NAMES: [&str; 12] = [
-	"Facundo",
-	"Lucca",
-	"Maximo",
-	"Juan",
-	"Pedro",
-	"Maria",
-	"Jose",
-	"Lucia",
-	"Carlos",
-	"Julieta",
-	"Martin",
-	"Agustina",
-];
+	fn entries(&self) -> &[&'static str] {
+		self.entries.get_or_init(|| self.text.lines().filter(|line| !line.is_empty()).collect())
+	}
 
-const FAKE_LAST_NAMES: [&str; 12] = [
-	"Villa",
-	"Salerno",
-	"Alvarez",
-	"Martinez",
-	"Perez",
-	"Sanchez",
-	"Romero",
-	"Suarez",
-	"Vazquez",
-	"Rojas",
-	"Acosta",
-	"Blanco",
-];
+	fn pick(&self, seed: usize) -> &'static str {
+		let entries = self.entries();
+		entries[seed % entries.len()]
+	}
+}
 
-const ADJECTIVES: [&str; 12] = [
-	"crazy",
-	"rambuctious",
-	"happy",
-	"sazzy",
-	"creepy",
-	"sexy",
-	"cool",
-	"tubular",
-	"radical",
-	"intriguing",
-	"boring",
-	"lame",
-];
+/// The names and places of a region, and how it writes phone numbers and addresses.
+#[derive(Debug)]
+pub struct Locale {
+	pub name: &'static str,
+	country: &'static str,
+	first_names: List,
+	last_names: List,
+	streets: List,
+	cities: List,
+	states: List,
+	/// Whether full names can have more than one last name.
+	compound_last_names: bool,
+	phone_number: fn(usize) -> String,
+	address: fn(&str, usize) -> String,
+	zip: fn(usize) -> String,
+}
 
-const ROLES: [&str; 12] = [
-	"superadmin",
-	"admin",
-	"editor",
-	"author",
-	"contributor",
-	"subscriber",
-	"customer",
-	"guest",
-	"visitor",
-	"banned",
-	"pending",
-	"deleted",
+/// The locales template variables can be generated in, the first one being the default.
+pub static LOCALES: [Locale; 3] = [
+	Locale {
+		name: "en_US",
+		country: "United States",
+		first_names: List::new(include_str!("fake/en_US/first_names.txt")),
+		last_names: List::new(include_str!("fake/en_US/last_names.txt")),
+		streets: List::new(include_str!("fake/en_US/streets.txt")),
+		cities: List::new(include_str!("fake/en_US/cities.txt")),
+		states: List::new(include_str!("fake/en_US/states.txt")),
+		compound_last_names: false,
+		phone_number: |seed| format!("({:03}) {:03}-{:04}", 200 + seed % 800, random::nth(seed, 1) % 1000, random::nth(seed, 2) % 10_000),
+		address: |street, seed| format!("{} {}", 1 + seed % 9999, street),
+		zip: |seed| format!("{:05}", 501 + seed % 99_450),
+	},
+	Locale {
+		name: "es_AR",
+		country: "Argentina",
+		first_names: List::new(include_str!("fake/es_AR/first_names.txt")),
+		last_names: List::new(include_str!("fake/es_AR/last_names.txt")),
+		streets: List::new(include_str!("fake/es_AR/streets.txt")),
+		cities: List::new(include_str!("fake/es_AR/cities.txt")),
+		states: List::new(include_str!("fake/es_AR/states.txt")),
+		compound_last_names: true,
+		phone_number: |seed| format!("+54 11 {:04}-{:04}", 2000 + seed % 8000, random::nth(seed, 1) % 10_000),
+		address: |street, seed| format!("{} {}", street, 1 + seed % 9999),
+		zip: |seed| format!("{:04}", 1000 + seed % 9000),
+	},
+	Locale {
+		name: "de_DE",
+		country: "Deutschland",
+		first_names: List::new(include_str!("fake/de_DE/first_names.txt")),
+		last_names: List::new(include_str!("fake/de_DE/last_names.txt")),
+		streets: List::new(include_str!("fake/de_DE/streets.txt")),
+		cities: List::new(include_str!("fake/de_DE/cities.txt")),
+		states: List::new(include_str!("fake/de_DE/states.txt")),
+		compound_last_names: false,
+		phone_number: |seed| format!("+49 30 {:08}", seed % 100_000_000),
+		address: |street, seed| format!("{} {}", street, 1 + seed % 200),
+		zip: |seed| format!("{:05}", 1067 + seed % 98_000),
+	},
 ];
 
-fn get_fake_name(seed: usize) -> &'static str {
-	FAKE_FIRST_NAMES[seed % FAKE_FIRST_NAMES.len()]
+/// The locale used when neither the configuration nor the template variable names one.
+pub fn default_locale() -> &'static Locale {
+	&LOCALES[0]
 }
 
-fn get_fake_last_name(seed: usize) -> &'static str {
-	FAKE_LAST_NAMES[seed % FAKE_LAST_NAMES.len()]
+pub fn find_locale(name: &str) -> Option<&'static Locale> {
+	LOCALES.iter().find(|locale| locale.name == name)
 }
 
-fn get_fake_role(seed: usize) -> &'static str {
-	ROLES[seed % ROLES.len()]
+static ADJECTIVES: List = List::new(include_str!("fake/adjectives.txt"));
+static NOUNS: List = List::new(include_str!("fake/nouns.txt"));
+static VERBS: List = List::new(include_str!("fake/verbs.txt"));
+static ROLES: List = List::new(include_str!("fake/roles.txt"));
+static COMPANIES: List = List::new(include_str!("fake/companies.txt"));
+static JOBS: List = List::new(include_str!("fake/jobs.txt"));
+static EMAIL_DOMAINS: List = List::new(include_str!("fake/email_domains.txt"));
+
+type Provider = fn(&Locale, usize) -> String;
+
+/// Providers for the template variables, looked up by their name.
+/// Adding a variable only requires adding its provider here.
+const PROVIDERS: [(&str, Provider); 19] = [
+	("FULL_NAME", get_fake_full_name),
+	("FIRST_NAME", |locale, seed| locale.first_names.pick(seed).to_string()),
+	("LAST_NAME", |locale, seed| locale.last_names.pick(seed).to_string()),
+	("PHONE_NUMBER", |locale, seed| (locale.phone_number)(seed)),
+	("EMAIL", get_fake_email),
+	("ADDRESS", |locale, seed| (locale.address)(locale.streets.pick(seed), random::nth(seed, 1))),
+	("CITY", |locale, seed| locale.cities.pick(seed).to_string()),
+	("STATE", |locale, seed| locale.states.pick(seed).to_string()),
+	("ZIP", |locale, seed| (locale.zip)(seed)),
+	("COUNTRY", |locale, _| locale.country.to_string()),
+	("COMPANY", |_, seed| COMPANIES.pick(seed).to_string()),
+	("JOB", |_, seed| JOBS.pick(seed).to_string()),
+	("ADJECTIVE", |_, seed| ADJECTIVES.pick(seed).to_string()),
+	("NOUN", |_, seed| NOUNS.pick(seed).to_string()),
+	("VERB", |_, seed| VERBS.pick(seed).to_string()),
+	("ROLE", |_, seed| ROLES.pick(seed).to_string()),
+	("FIELD.name", |_, seed| get_fake_field(seed).0.to_string()),
+	("FIELD.value", |locale, seed| (get_fake_field(seed).1)(locale, random::nth(seed, 1))),
+	("AGE", |_, seed| (18 + seed % 63).to_string()),
+];
+
+/// Fields of a person or a company, generated by `FIELD.name` and `FIELD.value`.
+const FIELDS: [(&str, &str); 12] = [
+	("first_name", "FIRST_NAME"),
+	("last_name", "LAST_NAME"),
+	("email", "EMAIL"),
+	("phone", "PHONE_NUMBER"),
+	("address", "ADDRESS"),
+	("city", "CITY"),
+	("state", "STATE"),
+	("zip", "ZIP"),
+	("country", "COUNTRY"),
+	("company", "COMPANY"),
+	("job", "JOB"),
+	("age", "AGE"),
+];
+
+fn get_fake_field(seed: usize) -> (&'static str, Provider) {
+	let (name, variable) = FIELDS[seed % FIELDS.len()];
+
+	(name, provider(variable).expect("Fields are generated by known variables"))
 }
 
-pub fn get_fake_full_name(seed: usize) -> String {
-	if seed % 2 == 0 {
-		format!("{} {}", get_fake_name(seed), get_fake_last_name(seed))
-	} else if seed % 3 == 0 {
-		format!("{} {} {} {}", get_fake_name(seed), get_fake_last_name(seed + 1), get_fake_last_name(seed), get_fake_last_name(seed + 2))
-	} else {
-		format!("{} {} {}", get_fake_name(seed), get_fake_last_name(seed + 1), get_fake_last_name(seed))
-	}
+fn provider(name: &str) -> Option<Provider> {
+	PROVIDERS.iter().find(|(provider, _)| *provider == name).map(|(_, generate)| *generate)
 }
 
-fn get_fake_field(seed: usize) -> &'static FakeField {
-	&FIELDS[seed % FIELDS.len()]
+/// Whether `name` is a template variable, without a locale suffix.
+pub fn is_variable(name: &str) -> bool {
+	provider(name).is_some()
 }
 
-pub fn get_fake_field_name(seed: usize) -> &'static str {
-	get_fake_field(seed).name
+/// Generates the value of the template variable `name`, or returns `None` if there is no such variable.
+/// The variable is generated in `locale` unless it names its own, like `FULL_NAME:es_AR`.
+pub fn get_fake_variable(name: &str, seed: usize, locale: &Locale) -> Option<String> {
+	let (name, locale) = match name.split_once(':') {
+		Some((name, locale)) => (name, find_locale(locale)?),
+		None => (name, locale),
+	};
+
+	provider(name).map(|generate| generate(locale, seed))
 }
 
-pub fn get_fake_field_value(seed: usize) -> &'static str {
-	get_fake_field(seed).values[seed % get_fake_field(seed).values.len()]
+pub fn get_fake_full_name(locale: &Locale, seed: usize) -> String {
+	let count = match seed % 6 {
+		_ if !locale.compound_last_names => 1,
+		0 | 2 | 4 => 1,
+		3 => 3,
+		_ => 2,
+	};
+
+	let mut name = locale.first_names.pick(seed).to_string();
+
+	for i in 1..=count {
+		name.push(' ');
+		name.push_str(locale.last_names.pick(random::nth(seed, i)));
+	}
+
+	name
 }
 
-pub fn get_fake_adjective(seed: usize) -> &'static str {
-	ADJECTIVES[seed % ADJECTIVES.len()]
+/// Writes a name with ASCII letters only, the way it would be written in an email address.
+fn ascii(name: &str) -> String {
+	name.to_lowercase().chars().filter(|c| !c.is_whitespace()).flat_map(|c| {
+		let replacement = match c {
+			'ä' => "ae",
+			'ö' => "oe",
+			'ü' => "ue",
+			'ß' => "ss",
+			'á' | 'à' => "a",
+			'é' | 'è' => "e",
+			'í' => "i",
+			'ó' => "o",
+			'ú' => "u",
+			'ñ' => "n",
+			c if c.is_ascii_alphanumeric() || c == '-' => return vec![c],
+			_ => "",
+		};
+
+		replacement.chars().collect()
+	}).collect()
 }
 
-pub fn get_fake_role_name(seed: usize) -> &'static str {
-	get_fake_role(seed)
+pub fn get_fake_email(locale: &Locale, seed: usize) -> String {
+	format!("{}.{}@{}", ascii(locale.first_names.pick(seed)), ascii(locale.last_names.pick(random::nth(seed, 1))), EMAIL_DOMAINS.pick(random::nth(seed, 2)))
 }
 
+/// Generates a version 4 UUID, with all of its random bits taken from the seed.
 pub fn get_fake_uuidv4(seed: usize) -> String {
-	const HEX_CHARS: [char; 16] = ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f'];
+	let high = random::nth(seed, 1) as u64;
+	let low = random::nth(seed, 2) as u64;
+
+	// The version and variant bits
+	let high = (high & 0xffff_ffff_ffff_0fff) | 0x4000;
+	let low = (low & 0x3fff_ffff_ffff_ffff) | 0x8000_0000_0000_0000;
+
+	format!("{:08x}-{:04x}-{:04x}-{:04x}-{:012x}", high >> 32, (high >> 16) & 0xffff, high & 0xffff, low >> 48, low & 0xffff_ffff_ffff)
+}
 
-	fn hex_char(i: usize) -> char {
-		HEX_CHARS[i % 16]
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_get_fake_variable() {
+		for locale in &LOCALES {
+			for name in ["FULL_NAME", "FIRST_NAME", "LAST_NAME", "PHONE_NUMBER", "EMAIL", "ADDRESS", "CITY", "STATE", "ZIP", "COUNTRY", "COMPANY", "JOB", "ADJECTIVE", "NOUN", "VERB", "ROLE", "FIELD.name", "FIELD.value", "AGE"] {
+				for seed in 0..32 {
+					let value = get_fake_variable(name, seed, locale).unwrap();
+					assert!(!value.is_empty());
+					assert_eq!(Some(value), get_fake_variable(name, seed, locale));
+				}
+			}
+		}
+
+		assert_eq!(get_fake_variable("NOT_A_VARIABLE", 0, default_locale()), None);
+		assert_eq!(get_fake_variable("FULL_NAME:xx_XX", 0, default_locale()), None);
+	}
+
+	#[test]
+	fn test_get_fake_uuidv4() {
+		let uuids = (0..1000).map(get_fake_uuidv4).collect::<std::collections::HashSet<_>>();
+		assert_eq!(uuids.len(), 1000);
+
+		for uuid in uuids {
+			assert_eq!(uuid.len(), 36);
+			assert_eq!(&uuid[14..15], "4");
+			assert!(["8", "9", "a", "b"].contains(&&uuid[19..20]));
+		}
 	}
 
-	format!("{}{}{}{}{}{}{}{}-{}{}{}{}-4{}{}{}-{}{}{}{}{}{}{}{}{}{}{}{}", hex_char(seed + 0), hex_char(seed + 18), hex_char(seed + 3), hex_char(seed + 99), hex_char(seed + 2), hex_char(seed + 18), hex_char(seed + 6), hex_char(seed + 7), hex_char(seed + 19), hex_char(seed + 9), hex_char(seed + 36), hex_char(seed + 23), hex_char(seed + 12), hex_char(seed + 11), hex_char(seed + 14), hex_char(seed + 15), hex_char(seed + 13), hex_char(seed + 17), hex_char(seed + 12), hex_char(seed + 9), hex_char(seed + 20), hex_char(seed + 21), hex_char(seed + 22), hex_char(seed + 5), hex_char(seed + 24), hex_char(seed + 25), hex_char(seed + 16))
+	#[test]
+	fn test_locales() {
+		for locale in &LOCALES {
+			for list in [&locale.first_names, &locale.last_names, &locale.streets, &locale.cities] {
+				assert!(list.entries().len() >= 100, "{}", locale.name);
+			}
+
+			for seed in 0..100 {
+				assert!(get_fake_email(locale, seed).is_ascii());
+			}
+		}
+
+		let es_ar = find_locale("es_AR").unwrap();
+		assert_eq!(get_fake_variable("COUNTRY:es_AR", 0, default_locale()), Some("Argentina".to_string()));
+		assert_eq!(get_fake_variable("CITY:es_AR", 7, default_locale()), get_fake_variable("CITY", 7, es_ar));
+		assert!(get_fake_variable("PHONE_NUMBER", 7, es_ar).unwrap().starts_with("+54"));
+
+		// Records shouldn't repeat after a handful of ids
+		let names = (0..1000).map(|seed| get_fake_full_name(default_locale(), random::scramble(seed))).collect::<std::collections::HashSet<_>>();
+		assert!(names.len() > 900, "{}", names.len());
+	}
 }
@@ -1,39 +1,425 @@
-use std::collections::HashMap;
+use std::{collections::{HashMap, HashSet}, sync::{Arc, Mutex, OnceLock, RwLock}, time::{Duration, SystemTime}};
 
-use routes::{ingest_routes, Route};
+use cli::{Commands, GenerateOptions, ServeOptions};
+use query::{parse_query_string, Query};
+use routes::{ingest_routes, Methods, Response, Route};
 use schemas::{Schema, ingest_schemas};
+use settings::{ingest_settings, Settings};
 use server_nano::Server;
-use values::{build_value, Context, StringExpressions};
+use store::{Store, StoreError};
+use values::{build_value, Context, DataTypes, ObjectExpressions, StringExpressions};
 
+mod cli;
+mod config;
 mod fake;
+mod query;
+mod random;
 mod schemas;
+mod settings;
 mod routes;
+mod store;
+mod validate;
 mod values;
 
-fn ingest_data(source: &serde_json::Value) -> (HashMap<String, Route>, HashMap<String, Schema>) {
+/// What a registered route does when it receives a request.
+enum Handlers {
+	/// Generates the response from the route's current definition.
+	Generate,
+	/// Lists or creates the items of a store.
+	Collection(Arc<Mutex<Store>>, Option<Query>),
+	/// Reads, updates or deletes a single item of a store.
+	Item(Arc<Mutex<Store>>),
+}
+
+/// Registers a handler on the server for the given method.
+/// The handler is expanded once per method so the closure's argument types can be inferred by each server call.
+macro_rules! register {
+	($app:expr, $method:expr, $path:expr, $handler:expr) => {
+		match $method {
+			Methods::Get => { $app.get($path, $handler); },
+			Methods::Post => { $app.post($path, $handler); },
+			Methods::Put => { $app.put($path, $handler); },
+			Methods::Patch => { $app.patch($path, $handler); },
+			Methods::Delete => { $app.delete($path, $handler); },
+		}
+	};
+}
+
+/// Most header lines with generated values that are kept in memory.
+/// Those can be different on every request, like `Location: /people/${this.id}`, so they'd otherwise grow without bound.
+const MAX_GENERATED_LINES: usize = 4096;
+
+/// Returns a `'static` copy of a header line, as the server requires them.
+/// Each distinct line is leaked only once and reused afterwards.
+/// Only `MAX_GENERATED_LINES` lines with generated values are leaked, after that `None` is returned for new ones.
+fn intern(line: String, generated: bool) -> Option<&'static str> {
+	static LINES: OnceLock<Mutex<(HashSet<&'static str>, usize)>> = OnceLock::new();
+
+	let mut lines = LINES.get_or_init(Default::default).lock().unwrap();
+	let (lines, generated_lines) = &mut *lines;
+
+	if let Some(line) = lines.get(line.as_str()) {
+		return Some(line);
+	}
+
+	if generated {
+		if *generated_lines == MAX_GENERATED_LINES {
+			return None;
+		}
+
+		*generated_lines += 1;
+
+		if *generated_lines == MAX_GENERATED_LINES {
+			eprintln!("Kept {} header lines with generated values, new ones won't be sent until a restart", MAX_GENERATED_LINES);
+		}
+	}
+
+	let line: &'static str = Box::leak(line.into_boxed_str());
+	lines.insert(line);
+
+	Some(line)
+}
+
+/// Whether a header value is the same on every request.
+fn is_literal(value: &DataTypes) -> bool {
+	match value {
+		DataTypes::String(expressions) => expressions.iter().all(|expression| matches!(expression, StringExpressions::Literal(_))),
+		_ => false,
+	}
+}
+
+/// Paginates, sorts and filters `items` with the query parameters in `path`.
+/// Returns the response body and the header lines to send with it.
+fn apply_query(path: &str, query: &Query, items: Vec<serde_json::Value>) -> (serde_json::Value, Vec<&'static str>) {
+	let (path, params) = parse_query_string(path);
+
+	let (rsp, headers) = query::apply(query, items, path, &params);
+
+	// The headers repeat the query parameters, which any client can make different on every request
+	(rsp, headers.into_iter().filter_map(|(name, value)| intern(format!("{}: {}", name, value), true)).collect())
+}
+
+fn reason(status: u16) -> &'static str {
+	match status {
+		200 => "OK",
+		201 => "Created",
+		202 => "Accepted",
+		204 => "No Content",
+		301 => "Moved Permanently",
+		302 => "Found",
+		304 => "Not Modified",
+		400 => "Bad Request",
+		401 => "Unauthorized",
+		403 => "Forbidden",
+		404 => "Not Found",
+		405 => "Method Not Allowed",
+		409 => "Conflict",
+		422 => "Unprocessable Entity",
+		429 => "Too Many Requests",
+		500 => "Internal Server Error",
+		502 => "Bad Gateway",
+		503 => "Service Unavailable",
+		_ => "",
+	}
+}
+
+/// The status code a store error is answered with.
+fn status(error: &StoreError) -> u16 {
+	match error {
+		StoreError::NotFound => 404,
+		StoreError::DuplicateId(_) => 409,
+	}
+}
+
+/// The routes, schemas and settings defined by a configuration.
+type Definitions = (HashMap<String, Route>, HashMap<String, Schema>, Settings);
+
+fn ingest_data(source: &serde_json::Value) -> Definitions {
 	let routes = ingest_routes(&source);
-	let schemas = ingest_schemas(&source);
+	let mut schemas = ingest_schemas(&source);
+
+	// References pick from the items of their schema's collection route, so they resolve to objects that route serves
+	let mut route_names = routes.keys().filter(|route_name| !route_name.contains(':')).collect::<Vec<_>>();
+	route_names.sort_by_key(|route_name| (route_name.len(), route_name.to_string()));
+
+	for route_name in route_names {
+		if let Some(Response { body: DataTypes::Array(item, length), .. }) = routes[route_name].methods.get(&Methods::Get) {
+			if let DataTypes::Object(ObjectExpressions::Schema(schema_name)) = item.as_ref() {
+				if let Some(schema) = schemas.get_mut(schema_name) {
+					schema.collection.get_or_insert_with(|| length.clone());
+				}
+			}
+		}
+	}
 
-	(routes, schemas)
+	(routes, schemas, ingest_settings(source))
+}
+
+/// Reads, validates and ingests the configuration, along with the files it imports.
+/// Returns the messages to print if it can't be read or has errors.
+fn load(data_path: &str) -> Result<Definitions, String> {
+	let config = config::read_config(data_path);
+
+	let mut errors = config.errors;
+
+	// Values defined twice are ignored by the merge, so they'd only add noise to the validation
+	if errors.is_empty() {
+		errors = validate::validate(&config.files, &config.source);
+	}
+
+	if !errors.is_empty() {
+		let mut message = format!("Found {} error(s) in {}:", errors.len(), data_path);
+
+		for error in errors {
+			message.push_str(&format!("\n  {}", error));
+		}
+
+		return Err(message);
+	}
+
+	Ok(ingest_data(&config.source))
+}
+
+/// Generates the response to a request, along with the header lines to send with it.
+/// The body is `None` for `204` responses, which have none.
+fn generate_response(schemas: &HashMap<String, Schema>, response: &Response, ctx: &Context, path: &str) -> (Vec<&'static str>, Option<serde_json::Value>) {
+	let mut headers = Vec::new();
+
+	for (name, value) in &response.headers {
+		let line = format!("{}: {}", name, build_value(schemas, value, ctx).as_str().unwrap_or(""));
+		headers.extend(intern(line, !is_literal(value)));
+	}
+
+	if response.status == 204 {
+		return (headers, None);
+	}
+
+	let rsp = build_value(schemas, &response.body, ctx);
+
+	let rsp = match (&response.query, rsp) {
+		(Some(query), serde_json::Value::Array(items)) => {
+			let (rsp, query_headers) = apply_query(path, query, items);
+			headers.extend(query_headers);
+			rsp
+		},
+		(_, rsp) => rsp,
+	};
+
+	(headers, Some(rsp))
 }
 
 fn main() {
-	let data_path = std::env::args().nth(1).expect("No data file path was provided");
+	let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+	let env = std::env::vars().collect::<HashMap<_, _>>();
+
+	let command = match cli::parse_args(&args, &env) {
+		Ok(command) => command,
+		Err(error) => {
+			eprintln!("{}\n\n{}", error, cli::USAGE);
+			std::process::exit(2);
+		}
+	};
+
+	let data_path = match &command {
+		Commands::Help => {
+			println!("{}", cli::USAGE);
+			return;
+		},
+		Commands::Serve(data_path, _) | Commands::Validate(data_path) | Commands::Generate(data_path, _) => data_path,
+	};
+
+	let (routes, schemas, settings) = match load(data_path) {
+		Ok(data) => data,
+		Err(message) => {
+			eprintln!("{}", message);
+			std::process::exit(1);
+		}
+	};
+
+	match &command {
+		Commands::Serve(_, options) => serve(data_path, routes, schemas, settings, options),
+		Commands::Validate(_) => println!("{} is valid", data_path),
+		Commands::Generate(_, options) => match generate(&routes, &schemas, &settings, options) {
+			Ok(rsp) => println!("{}", serde_json::to_string_pretty(&rsp).unwrap()),
+			Err(error) => {
+				eprintln!("{}", error);
+				std::process::exit(1);
+			}
+		},
+		Commands::Help => {},
+	}
+}
+
+/// Generates the response of the route that matches `options.route`, as the server would.
+fn generate(routes: &HashMap<String, Route>, schemas: &HashMap<String, Schema>, settings: &Settings, options: &GenerateOptions) -> Result<serde_json::Value, String> {
+	let method = Methods::parse(&options.method).ok_or_else(|| format!("Unknown method `{}`", options.method))?;
+
+	let (path, _) = parse_query_string(&options.route);
+
+	// Prefer the most specific route, like `/people/positions` over `/people/:id`
+	let (route, mut params) = routes.iter()
+		.filter_map(|(route_name, route)| cli::match_route(route_name, path).map(|params| (route, params)))
+		.min_by_key(|(route, params)| (params.iter().filter(|(name, value)| format!(":{}", name) != *value).count(), route.name.clone()))
+		.ok_or_else(|| format!("No route matches `{}`", path))?;
+
+	if let (Some(id), Some((_, value))) = (&options.id, params.last_mut()) {
+		*value = id.clone();
+	}
+
+	if let Some((name, _)) = params.iter().find(|(name, value)| format!(":{}", name) == *value) {
+		return Err(format!("Route parameter `{}` has no value, set it in the route or with `--id`", name));
+	}
+
+	let response = route.methods.get(&method).ok_or_else(|| format!("`{}` doesn't respond to {}", route.name, options.method.to_uppercase()))?;
+
+	let ctx = Context::from_params(params, options.seed, options.scale, settings);
+
+	let (_, rsp) = generate_response(schemas, response, &ctx, &options.route);
+
+	Ok(rsp.unwrap_or(serde_json::Value::Null))
+}
+
+/// How often the configuration file is checked for changes with `--watch`.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The stores of the server, along with the routes that serve their collections.
+type Stores = Vec<(String, Arc<Mutex<Store>>)>;
+
+/// Loads the configuration again and swaps it into `definitions`, filling the stores again from their new definitions.
+/// Returns warnings about the parts of the new configuration that can't be served until a restart.
+fn reload(data_path: &str, definitions: &RwLock<Definitions>, registered: &HashSet<(String, Methods)>, stores: &Stores) -> Result<Vec<String>, String> {
+	let (routes, schemas, settings) = load(data_path)?;
 
-	let data = std::fs::read_to_string(data_path).expect("Failed to read data file");
+	let mut unregistered = routes.iter()
+		.flat_map(|(route_name, route)| route.methods.keys().map(move |method| (route_name.clone(), *method)))
+		.filter(|route| !registered.contains(route))
+		.collect::<Vec<_>>();
+	unregistered.sort_by_key(|(route_name, method)| (route_name.clone(), format!("{:?}", method)));
 
-    let source = serde_json5::from_str::<serde_json::Value>(&data).unwrap();
+	let mut warnings = unregistered.into_iter()
+		.map(|(route_name, method)| format!("{} {} is new and is only served after a restart", format!("{:?}", method).to_uppercase(), route_name))
+		.collect::<Vec<_>>();
 
-    let (routes, schemas) = ingest_data(&source);
+	let mut new_stores = routes.iter()
+		.filter(|(route_name, route)| route.store && !stores.iter().any(|(store, _)| store == *route_name))
+		.map(|(route_name, _)| route_name.clone())
+		.collect::<Vec<_>>();
+	new_stores.sort();
+
+	warnings.extend(new_stores.into_iter().map(|route_name| format!("{} is now a store, but keeps being generated until a restart", route_name)));
+
+	// Every store is filled before any is replaced, so a store that can't be filled keeps the previous configuration
+	let mut filled = Vec::new();
+
+	for (route_name, store) in stores {
+		let definition = routes.get(route_name).filter(|route| route.store).and_then(|route| route.methods.get(&Methods::Get));
+
+		let Some(Response { body: DataTypes::Array(item, length), .. }) = definition else {
+			warnings.push(format!("{} is no longer a store, but keeps being served from one until a restart", route_name));
+			continue;
+		};
+
+		let mut new_store = store.lock().unwrap().clone();
+		new_store.reset(&schemas, item, length, &settings).map_err(|error| format!("Failed to fill the store of {}: {}", route_name, error))?;
+		filled.push((store, new_store));
+	}
+
+	*definitions.write().unwrap() = (routes, schemas, settings);
+
+	for (store, new_store) in filled {
+		*store.lock().unwrap() = new_store;
+	}
+
+	Ok(warnings)
+}
 
-	let scale = std::env::args().nth(2).map(|scale| scale.parse::<usize>().unwrap()).unwrap_or(16);
-	let seed = std::env::args().nth(3).map(|seed| seed.parse::<usize>().unwrap()).unwrap_or(0);
+/// Polls the modification times of the configuration files and reloads them when any changes.
+/// A configuration with errors is reported and the previous one keeps being served.
+fn watch(data_path: String, definitions: Arc<RwLock<Definitions>>, registered: HashSet<(String, Methods)>, stores: Stores) {
+	// The configuration path itself is included so files added to, or removed from, a directory are noticed
+	let modified = |paths: &[String]| paths.iter().map(|path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()).collect::<Vec<Option<SystemTime>>>();
+	let files = |data_path: &str| [vec![data_path.to_string()], config::read_config(data_path).paths].concat();
+
+	std::thread::spawn(move || {
+		let mut paths = files(&data_path);
+		let mut last_modified = modified(&paths);
+
+		loop {
+			std::thread::sleep(WATCH_INTERVAL);
+
+			if modified(&paths) == last_modified {
+				continue;
+			}
+
+			// Imports may have changed too
+			paths = files(&data_path);
+			last_modified = modified(&paths);
+
+			match reload(&data_path, &definitions, &registered, &stores) {
+				Ok(warnings) => {
+					println!("Reloaded {}", data_path);
+
+					for warning in warnings {
+						println!("  Warning: {}", warning);
+					}
+				},
+				Err(message) => eprintln!("{}\nKeeping the previous configuration", message),
+			}
+		}
+	});
+}
+
+fn serve(data_path: &str, routes: HashMap<String, Route>, schemas: HashMap<String, Schema>, settings: Settings, options: &ServeOptions) {
+	let (scale, seed) = (options.scale, options.seed);
 
 	let mut app = Server::new();
 
+	let mut handlers: Vec<(String, Methods, Handlers)> = Vec::new();
+	let mut stores = Stores::new();
+
+	// Store backed routes serve the collection and its items, taking precedence over generated responses
+	for (route_name, route) in routes.iter().filter(|(_, route)| route.store) {
+		if let Some(Response { body: DataTypes::Array(item, length), query, .. }) = route.methods.get(&Methods::Get) {
+			let store = match Store::seed(&schemas, item, length, &Context{ id: None, params: Vec::new(), seed, root: seed, size: scale, settings: &settings }) {
+				Ok(store) => Arc::new(Mutex::new(store)),
+				Err(error) => {
+					eprintln!("Failed to fill the store of {}: {}", route_name, error);
+					std::process::exit(1);
+				}
+			};
+
+			stores.push((route_name.clone(), store.clone()));
+
+			for method in [Methods::Get, Methods::Post] {
+				handlers.push((route_name.clone(), method, Handlers::Collection(store.clone(), query.clone())));
+			}
+
+			for method in [Methods::Get, Methods::Put, Methods::Patch, Methods::Delete] {
+				handlers.push((format!("{}/:id", route_name), method, Handlers::Item(store.clone())));
+			}
+		}
+	}
+
+	for (route_name, route) in &routes {
+		for method in route.methods.keys() {
+			if !handlers.iter().any(|(path, m, _)| path == route_name && m == method) {
+				handlers.push((route_name.clone(), *method, Handlers::Generate));
+			}
+		}
+	}
+
+	let registered = handlers.iter().map(|(route_name, method, _)| (route_name.clone(), *method)).collect::<HashSet<_>>();
+
+	// Handlers read the definitions on every request, so a reload swaps all of them at once
+	let definitions = Arc::new(RwLock::new((routes, schemas, settings)));
+
+	if options.watch {
+		watch(data_path.to_string(), definitions.clone(), registered, stores);
+	}
+
 	// Put routes that contain colons at the end
-	let mut routes = routes.into_iter().collect::<Vec<_>>();
-	routes.sort_by(|(a, _), (b, _)| {
+	handlers.sort_by(|(a, _, _), (b, _, _)| {
         let a_has_colon = a.split('/').last().unwrap_or("").contains(':');
         let b_has_colon = b.split('/').last().unwrap_or("").contains(':');
 
@@ -46,32 +432,160 @@ fn main() {
         }
     });
 
-	for (route_name, route) in routes {
-		let schemas = schemas.clone();
+	for (route_name, method, handler) in handlers {
+		let definitions = definitions.clone();
+		let name = route_name.clone();
+
+		let parameters = route_name.split('/').filter_map(|segment| segment.strip_prefix(':')).map(|parameter| parameter.to_string()).collect::<Vec<_>>();
+
+		register!(app, method, &route_name, move |req, res| {
+			match &handler {
+				Handlers::Generate => {
+					let params = parameters.iter().filter_map(|parameter| req.parameter(parameter).map(|value| (parameter.clone(), value.to_string()))).collect();
+
+					let (routes, schemas, settings) = &*definitions.read().unwrap();
+
+					let ctx = Context::from_params(params, seed, scale, settings);
+
+					// The route may have been removed by a reload
+					let Some(response) = routes.get(&name).and_then(|route| route.methods.get(&method)) else {
+						res.status_code(404, reason(404));
+						return res.json(&serde_json::Value::Null);
+					};
+
+					let (headers, rsp) = generate_response(schemas, response, &ctx, req.path());
+
+					for header in headers {
+						res.header(header);
+					}
+
+					res.status_code(response.status as usize, reason(response.status));
+
+					match rsp {
+						Some(rsp) => res.json(&rsp),
+						None => res.send(""),
+					}
+				},
+				Handlers::Collection(store, query) => {
+					if method == Methods::Post {
+						let Ok(body) = req.json_body::<serde_json::Value>() else {
+							res.status_code(400, reason(400));
+							return res.json(&serde_json::Value::Null);
+						};
+
+						let (_, schemas, settings) = &*definitions.read().unwrap();
+
+						match store.lock().unwrap().create(schemas, settings, body) {
+							Ok(item) => {
+								res.status_code(201, reason(201));
+								res.json(&item)
+							},
+							Err(error) => {
+								res.status_code(status(&error) as usize, reason(status(&error)));
+								res.json(&serde_json::Value::Null)
+							},
+						}
+					} else {
+						let items = store.lock().unwrap().list();
+
+						let rsp = match query {
+							Some(query) => {
+								let (rsp, headers) = apply_query(req.path(), query, items);
+
+								for header in headers {
+									res.header(header);
+								}
+
+								rsp
+							},
+							None => serde_json::Value::Array(items),
+						};
+
+						res.json(&rsp)
+					}
+				},
+				Handlers::Item(store) => {
+					let id = req.parameter("id").unwrap_or("");
+
+					let body = match method {
+						Methods::Put | Methods::Patch => match req.json_body::<serde_json::Value>() {
+							Ok(body) => body,
+							Err(_) => {
+								res.status_code(400, reason(400));
+								return res.json(&serde_json::Value::Null);
+							}
+						},
+						_ => serde_json::Value::Null,
+					};
+
+					let mut store = store.lock().unwrap();
+
+					let item = match method {
+						Methods::Put => store.update(id, body),
+						Methods::Patch => store.patch(id, body),
+						Methods::Delete => store.delete(id).ok_or(StoreError::NotFound),
+						_ => store.get(id).ok_or(StoreError::NotFound),
+					};
+
+					match item {
+						Ok(item) => res.json(&item),
+						Err(error) => {
+							res.status_code(status(&error) as usize, reason(status(&error)));
+							res.json(&serde_json::Value::Null)
+						}
+					}
+				},
+			}
+		});
+	}
 
-		app.get(&route_name, move |req, res| {
-			let id = req.parameter("id").map(|id| id.to_string());
+	let port = match options.port {
+		// Port 0 is resolved up front, as the server doesn't report the port it ends up listening on.
+		// The port is free again once the probe is dropped, so another process could take it before the server binds it.
+		0 => match std::net::TcpListener::bind((options.host.as_str(), 0)).and_then(|listener| listener.local_addr()) {
+			Ok(address) => address.port(),
+			Err(error) => {
+				eprintln!("Failed to find a free port on {}: {}", options.host, error);
+				std::process::exit(1);
+			}
+		},
+		port => port,
+	};
 
-			let rsp = build_value(&schemas, &route.response, &Context{ id, seed, size: scale });
+	let address = format!("{}:{}", options.host, port);
 
-			res.json(&rsp)
-		});
-	}
+	// The server doesn't report when it has bound the address either, so it's printed once the server accepts connections
+	let reachable = format!("{}:{}", match options.host.as_str() { "0.0.0.0" => "127.0.0.1", host => host }, port);
+	let listening = address.clone();
+
+	std::thread::spawn(move || {
+		while std::net::TcpStream::connect(&reachable).is_err() {
+			std::thread::sleep(Duration::from_millis(20));
+		}
+
+		println!("Listening on {}", listening);
+	});
+
+	if let Err(error) = app.listen(address.as_str()) {
+		match options.port {
+			0 => eprintln!("Failed to listen on {}, another process may have taken the free port first: {}", address, error),
+			_ => eprintln!("Failed to listen on {}: {}", address, error),
+		}
 
-	app.listen("0.0.0.0:80").unwrap();
+		std::process::exit(1);
+	}
 }
 
 fn scan_until<'a>(s: &'a str, m: &'a str) -> (&'a str, &'a str) {
-	let mut i = 0;
-	while i < s.len() {
-		if s[i..].starts_with(m) {
-			return (&s[..i], &s[i..]);
-		}
-		i += 1;
+	match s.find(m) {
+		Some(i) => (&s[..i], &s[i..]),
+		None => (s, ""),
 	}
-	(&s, "")
 }
 
+/// Parses a template into its expressions.
+/// Malformed expressions don't fail: unterminated ones are kept as literals and ranges with invalid bounds as variables.
+/// `validate::check_template` reports them.
 pub fn parse_template(template: &str) -> Vec<StringExpressions> {
 	let mut result = Vec::new();
 	let mut start = template;
@@ -79,11 +593,16 @@ pub fn parse_template(template: &str) -> Vec<StringExpressions> {
 	while !start.is_empty() {
 		if start.starts_with("${") {
 			let (content, rest) = scan_until(&start[2..], "}");
+
+			if rest.is_empty() {
+				result.push(StringExpressions::Literal(start.to_string()));
+				break;
+			}
+
 			let variable = content;
-			if variable.contains("..") {
-				let range = variable.split("..").collect::<Vec<&str>>();
-				let min = range[0].parse::<i64>().unwrap();
-				let max = range[1].parse::<i64>().unwrap();
+			let range = variable.split_once("..").and_then(|(min, max)| Some((min.trim().parse::<i64>().ok()?, max.trim().parse::<i64>().ok()?)));
+
+			if let Some((min, max)) = range {
 				result.push(StringExpressions::Range(min, max));
 			} else {
 				result.push(StringExpressions::Variable(variable.to_string()));
@@ -135,6 +654,12 @@ mod tests {
 			let result = parse_template(template);
 			assert_eq!(result, vec![StringExpressions::Range(0, 50), StringExpressions::Literal(" ".to_string()), StringExpressions::Range(50, 100)]);
 		}
+
+		{
+			let template = "${1 .. 5}";
+			let result = parse_template(template);
+			assert_eq!(result, vec![StringExpressions::Range(1, 5)]);
+		}
 	}
 
 	#[test]
@@ -199,4 +724,105 @@ mod tests {
 			assert_eq!(result, vec![StringExpressions::Literal("My name is John".to_string())]);
 		}
 	}
+
+	#[test]
+	fn parse_malformed() {
+		{
+			let template = "Hello ${FULL_NAME";
+			let result = parse_template(template);
+			assert_eq!(result, vec![StringExpressions::Literal("Hello ".to_string()), StringExpressions::Literal("${FULL_NAME".to_string())]);
+		}
+
+		{
+			let template = "${a..b}";
+			let result = parse_template(template);
+			assert_eq!(result, vec![StringExpressions::Variable("a..b".to_string())]);
+		}
+
+		{
+			let template = "Olá ${FULL_NAME}";
+			let result = parse_template(template);
+			assert_eq!(result, vec![StringExpressions::Literal("Olá ".to_string()), StringExpressions::Variable("FULL_NAME".to_string())]);
+		}
+	}
+
+	#[test]
+	fn test_intern() {
+		let line = intern("X-Mock: true".to_string(), false).unwrap();
+		assert!(std::ptr::eq(line, intern("X-Mock: true".to_string(), false).unwrap()));
+
+		assert!(is_literal(&DataTypes::String(parse_template("16"))));
+		assert!(!is_literal(&DataTypes::String(parse_template("/people/${this.id}"))));
+	}
+
+	#[test]
+	fn test_reload() {
+		let data_path = std::env::temp_dir().join(format!("moquist-reload-{}.json5", std::process::id()));
+		let data_path = data_path.to_str().unwrap();
+
+		std::fs::write(data_path, r#"{ schemas: {}, routes: { '/people': { response: { status: 200 } } } }"#).unwrap();
+		let definitions = RwLock::new(load(data_path).unwrap());
+		let registered = HashSet::from([("/people".to_string(), Methods::Get)]);
+
+		std::fs::write(data_path, r#"{ schemas: {}, routes: { '/people': { response: { status: 201 } }, '/orgs': {} } }"#).unwrap();
+		assert_eq!(reload(data_path, &definitions, &registered, &Stores::new()), Ok(vec!["GET /orgs is new and is only served after a restart".to_string()]));
+		assert_eq!(definitions.read().unwrap().0["/people"].methods[&Methods::Get].status, 201);
+
+		// A configuration with errors keeps the previous one
+		std::fs::write(data_path, r#"{ schemas: {}, routes: { '/people': { response: { status: 999 } } } }"#).unwrap();
+		assert!(reload(data_path, &definitions, &registered, &Stores::new()).is_err());
+		assert_eq!(definitions.read().unwrap().0["/people"].methods[&Methods::Get].status, 201);
+
+		std::fs::remove_file(data_path).unwrap();
+	}
+
+	#[test]
+	fn test_reload_stores() {
+		let data_path = std::env::temp_dir().join(format!("moquist-reload-stores-{}.json5", std::process::id()));
+		let data_path = data_path.to_str().unwrap();
+
+		let config = |name: &str, store: bool| format!(r#"{{ schemas: {{ Person: {{ fields: {{ id: {{ template: "${{this.id}}" }}, name: "{}" }} }} }}, routes: {{ '/people': {{ response: "Person[]", store: {} }} }} }}"#, name, store);
+
+		std::fs::write(data_path, config("John", true)).unwrap();
+		let (routes, schemas, settings) = load(data_path).unwrap();
+		let Some(Response { body: DataTypes::Array(item, length), .. }) = routes["/people"].methods.get(&Methods::Get) else { panic!() };
+
+		let store = Store::seed(&schemas, item, length, &Context{ id: None, params: Vec::new(), seed: 0, root: 0, size: 4, settings: &settings }).unwrap();
+		let stores = vec![("/people".to_string(), Arc::new(Mutex::new(store)))];
+		let registered = HashSet::from([("/people".to_string(), Methods::Get)]);
+		let definitions = RwLock::new((routes, schemas, settings));
+
+		stores[0].1.lock().unwrap().create(&definitions.read().unwrap().1, &Settings::default(), serde_json::json!({})).unwrap();
+
+		// Stores are filled again from the new configuration
+		std::fs::write(data_path, config("Jane", true)).unwrap();
+		assert_eq!(reload(data_path, &definitions, &registered, &stores), Ok(vec![]));
+		assert_eq!(stores[0].1.lock().unwrap().list(), (0..4).map(|id| serde_json::json!({ "id": id.to_string(), "name": "Jane" })).collect::<Vec<_>>());
+
+		std::fs::write(data_path, config("Jim", false)).unwrap();
+		assert_eq!(reload(data_path, &definitions, &registered, &stores), Ok(vec!["/people is no longer a store, but keeps being served from one until a restart".to_string()]));
+		assert_eq!(stores[0].1.lock().unwrap().list()[0]["name"], "Jane");
+
+		// Routes that weren't stores when the server started keep being generated
+		std::fs::write(data_path, config("Joe", true)).unwrap();
+		assert_eq!(reload(data_path, &definitions, &registered, &Stores::new()), Ok(vec!["/people is now a store, but keeps being generated until a restart".to_string()]));
+
+		std::fs::remove_file(data_path).unwrap();
+	}
+
+	#[test]
+	fn test_generate_settings() {
+		let source = serde_json5::from_str(r#"{
+			locale: "de_DE",
+			dictionaries: { branches: ["Mitte"] },
+			schemas: { Person: { fields: { country: { template: "${COUNTRY}" }, born: { template: "${COUNTRY:es_AR}" }, branch: { template: "${dict.branches}" } } } },
+			routes: { "/people/:id": { response: "Person" } },
+		}"#).unwrap();
+
+		let (routes, schemas, settings) = ingest_data(&source);
+		assert_eq!(settings.locale.name, "de_DE");
+
+		let options = GenerateOptions { route: "/people/3".to_string(), method: "get".to_string(), id: None, scale: 16, seed: 0 };
+		assert_eq!(generate(&routes, &schemas, &settings, &options), Ok(serde_json::json!({ "country": "Deutschland", "born": "Argentina", "branch": "Mitte" })));
+	}
 }
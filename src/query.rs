@@ -0,0 +1,271 @@
+/// How pagination metadata is sent along with a page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Styles {
+	/// The page is wrapped in an object with the items under `data` and the metadata next to them.
+	Envelope,
+	/// The page is sent as is, with `X-Total-Count` and `Link` headers.
+	Headers,
+}
+
+/// Pagination, sorting and filtering options of a route that responds with an array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+	pub style: Styles,
+	/// Page size used when a request doesn't set `pageSize` or `limit`. If `None` requests without them get every item.
+	pub page_size: Option<usize>,
+}
+
+/// Reads the `query` key of a route response.
+pub fn ingest_query(source: &serde_json::Value) -> Option<Query> {
+	let style = |style: &str| match style {
+		"envelope" => Styles::Envelope,
+		_ => Styles::Headers,
+	};
+
+	match source {
+		serde_json::Value::Bool(true) => Some(Query { style: Styles::Headers, page_size: None }),
+		serde_json::Value::String(s) => Some(Query { style: style(s), page_size: None }),
+		serde_json::Value::Object(query) => Some(Query {
+			style: query.get("style").and_then(|s| s.as_str()).map_or(Styles::Headers, style),
+			page_size: query.get("pageSize").and_then(|size| size.as_u64()).map(|size| size as usize),
+		}),
+		_ => None,
+	}
+}
+
+/// Splits a request path into the path and its decoded query parameters.
+pub fn parse_query_string(path: &str) -> (&str, Vec<(String, String)>) {
+	let Some((path, query)) = path.split_once('?') else {
+		return (path, Vec::new());
+	};
+
+	let params = query.split('&').filter(|pair| !pair.is_empty()).map(|pair| {
+		let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+		(decode(key), decode(value))
+	}).collect();
+
+	(path, params)
+}
+
+fn decode(s: &str) -> String {
+	let bytes = s.as_bytes();
+	let mut decoded = Vec::with_capacity(bytes.len());
+
+	let mut i = 0;
+	while i < bytes.len() {
+		match bytes[i] {
+			b'+' => decoded.push(b' '),
+			b'%' if i + 2 < bytes.len() => {
+				match std::str::from_utf8(&bytes[i + 1..i + 3]).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+					Some(byte) => {
+						decoded.push(byte);
+						i += 2;
+					},
+					None => decoded.push(b'%'),
+				}
+			},
+			byte => decoded.push(byte),
+		}
+		i += 1;
+	}
+
+	String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn encode(s: &str) -> String {
+	s.bytes().map(|byte| match byte {
+		b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+		byte => format!("%{:02X}", byte),
+	}).collect()
+}
+
+const RESERVED: [&str; 5] = ["page", "pageSize", "offset", "limit", "sort"];
+
+/// Looks up a possibly dotted field, like `address.city`, in an item.
+fn lookup<'a>(item: &'a serde_json::Value, field: &str) -> Option<&'a serde_json::Value> {
+	field.split('.').try_fold(item, |value, key| value.get(key))
+}
+
+fn matches(value: Option<&serde_json::Value>, expected: &str) -> bool {
+	match value {
+		Some(serde_json::Value::String(value)) => value == expected,
+		Some(serde_json::Value::Null) | None => expected == "null",
+		Some(value) => serde_json::from_str::<serde_json::Value>(expected).is_ok_and(|expected| expected == *value),
+	}
+}
+
+fn compare(a: Option<&serde_json::Value>, b: Option<&serde_json::Value>) -> std::cmp::Ordering {
+	match (a, b) {
+		(Some(serde_json::Value::Number(a)), Some(serde_json::Value::Number(b))) => {
+			a.as_f64().partial_cmp(&b.as_f64()).unwrap_or(std::cmp::Ordering::Equal)
+		},
+		(Some(serde_json::Value::String(a)), Some(serde_json::Value::String(b))) => a.cmp(b),
+		(Some(a), Some(b)) => a.to_string().cmp(&b.to_string()),
+		(None, Some(_)) => std::cmp::Ordering::Less,
+		(Some(_), None) => std::cmp::Ordering::Greater,
+		(None, None) => std::cmp::Ordering::Equal,
+	}
+}
+
+fn with_params(path: &str, params: &[(String, String)], replace: &[(&str, usize)]) -> String {
+	let mut pairs = params.iter().filter(|(key, _)| !replace.iter().any(|(r, _)| r == key)).map(|(key, value)| format!("{}={}", encode(key), encode(value))).collect::<Vec<_>>();
+	pairs.extend(replace.iter().map(|(key, value)| format!("{}={}", key, value)));
+
+	format!("{}?{}", path, pairs.join("&"))
+}
+
+/// Filters, sorts and paginates `items` according to the request's query parameters.
+/// Returns the response body and the headers to send with it.
+pub fn apply(query: &Query, mut items: Vec<serde_json::Value>, path: &str, params: &[(String, String)]) -> (serde_json::Value, Vec<(String, String)>) {
+	let get = |key: &str| params.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+	let get_number = |key: &str| get(key).and_then(|v| v.parse::<usize>().ok());
+
+	for (field, expected) in params.iter().filter(|(key, _)| !RESERVED.contains(&key.as_str())) {
+		items.retain(|item| matches(lookup(item, field), expected));
+	}
+
+	if let Some(sort) = get("sort") {
+		for field in sort.split(',').rev().filter(|field| !field.is_empty()) {
+			let (field, descending) = match field.strip_prefix('-') {
+				Some(field) => (field, true),
+				None => (field, false),
+			};
+
+			items.sort_by(|a, b| {
+				let ordering = compare(lookup(a, field), lookup(b, field));
+				if descending { ordering.reverse() } else { ordering }
+			});
+		}
+	}
+
+	let total = items.len();
+	let mut headers = vec![("X-Total-Count".to_string(), total.to_string())];
+	let mut metadata = serde_json::Map::new();
+	metadata.insert("total".to_string(), total.into());
+
+	let mut links = Vec::new();
+
+	if get("offset").is_some() || get("limit").is_some() {
+		let offset = get_number("offset").unwrap_or(0);
+		let limit = get_number("limit").or(query.page_size).unwrap_or(total);
+
+		items = items.into_iter().skip(offset).take(limit).collect();
+
+		metadata.insert("offset".to_string(), offset.into());
+		metadata.insert("limit".to_string(), limit.into());
+
+		if limit > 0 {
+			if offset.saturating_add(limit) < total {
+				links.push((with_params(path, params, &[("offset", offset + limit), ("limit", limit)]), "next"));
+			}
+			if offset > 0 {
+				links.push((with_params(path, params, &[("offset", offset.saturating_sub(limit)), ("limit", limit)]), "prev"));
+			}
+		}
+	} else if let Some(page_size) = get_number("pageSize").or(query.page_size) {
+		let page = get_number("page").unwrap_or(1).max(1);
+		let last = total.div_ceil(page_size.max(1)).max(1);
+
+		items = items.into_iter().skip((page - 1).saturating_mul(page_size)).take(page_size).collect();
+
+		metadata.insert("page".to_string(), page.into());
+		metadata.insert("pageSize".to_string(), page_size.into());
+
+		if page_size > 0 {
+			links.push((with_params(path, params, &[("page", 1), ("pageSize", page_size)]), "first"));
+			if page > 1 {
+				links.push((with_params(path, params, &[("page", page - 1), ("pageSize", page_size)]), "prev"));
+			}
+			if page < last {
+				links.push((with_params(path, params, &[("page", page + 1), ("pageSize", page_size)]), "next"));
+			}
+			links.push((with_params(path, params, &[("page", last), ("pageSize", page_size)]), "last"));
+		}
+	}
+
+	match query.style {
+		Styles::Envelope => {
+			metadata.insert("data".to_string(), serde_json::Value::Array(items));
+			(serde_json::Value::Object(metadata), Vec::new())
+		},
+		Styles::Headers => {
+			if !links.is_empty() {
+				headers.push(("Link".to_string(), links.iter().map(|(link, rel)| format!("<{}>; rel=\"{}\"", link, rel)).collect::<Vec<_>>().join(", ")));
+			}
+
+			(serde_json::Value::Array(items), headers)
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn people() -> Vec<serde_json::Value> {
+		(0..10).map(|i| serde_json::json!({ "id": i, "name": format!("Person {}", 9 - i), "role": if i % 2 == 0 { "admin" } else { "guest" } })).collect()
+	}
+
+	#[test]
+	fn test_parse_query_string() {
+		assert_eq!(parse_query_string("/people"), ("/people", vec![]));
+		assert_eq!(parse_query_string("/people?page=2&name=John%20Doe&q=a+b"), ("/people", vec![
+			("page".to_string(), "2".to_string()),
+			("name".to_string(), "John Doe".to_string()),
+			("q".to_string(), "a b".to_string()),
+		]));
+	}
+
+	#[test]
+	fn test_ingest_query() {
+		assert_eq!(ingest_query(&serde_json::json!(true)), Some(Query { style: Styles::Headers, page_size: None }));
+		assert_eq!(ingest_query(&serde_json::json!("envelope")), Some(Query { style: Styles::Envelope, page_size: None }));
+		assert_eq!(ingest_query(&serde_json::json!({ "style": "envelope", "pageSize": 5 })), Some(Query { style: Styles::Envelope, page_size: Some(5) }));
+		assert_eq!(ingest_query(&serde_json::json!(false)), None);
+	}
+
+	#[test]
+	fn test_apply_pages() {
+		let query = Query { style: Styles::Headers, page_size: None };
+
+		let (_, params) = parse_query_string("/people?page=2&pageSize=3");
+		let (body, headers) = apply(&query, people(), "/people", &params);
+		assert_eq!(body.as_array().unwrap().iter().map(|p| p["id"].as_i64().unwrap()).collect::<Vec<_>>(), vec![3, 4, 5]);
+		assert_eq!(headers[0], ("X-Total-Count".to_string(), "10".to_string()));
+		assert_eq!(headers[1].1, r#"</people?page=1&pageSize=3>; rel="first", </people?page=1&pageSize=3>; rel="prev", </people?page=3&pageSize=3>; rel="next", </people?page=4&pageSize=3>; rel="last""#);
+
+		let (_, params) = parse_query_string("/people?offset=8&limit=5");
+		let (body, _) = apply(&query, people(), "/people", &params);
+		assert_eq!(body.as_array().unwrap().len(), 2);
+
+		let (body, headers) = apply(&query, people(), "/people", &[]);
+		assert_eq!(body.as_array().unwrap().len(), 10);
+		assert_eq!(headers.len(), 1);
+
+		// Huge values don't overflow
+		let max = usize::MAX.to_string();
+
+		for query_string in [format!("?page={}&pageSize={}", max, max), format!("?page={}&pageSize=3", max), format!("?offset={}&limit={}", max, max), format!("?offset=2&limit={}", max)] {
+			let (_, params) = parse_query_string(&query_string);
+			let (body, _) = apply(&query, people(), "/people", &params);
+			assert!(body.as_array().unwrap().len() <= 10);
+		}
+	}
+
+	#[test]
+	fn test_apply_sort_and_filter() {
+		let query = Query { style: Styles::Envelope, page_size: Some(2) };
+
+		let (_, params) = parse_query_string("/people?role=admin&sort=name");
+		let (body, headers) = apply(&query, people(), "/people", &params);
+		assert!(headers.is_empty());
+		assert_eq!(body["total"], 5);
+		assert_eq!(body["page"], 1);
+		assert_eq!(body["pageSize"], 2);
+		assert_eq!(body["data"].as_array().unwrap().iter().map(|p| p["id"].as_i64().unwrap()).collect::<Vec<_>>(), vec![8, 6]);
+
+		let (_, params) = parse_query_string("/people?sort=-id&pageSize=3");
+		let (body, _) = apply(&query, people(), "/people", &params);
+		assert_eq!(body["data"].as_array().unwrap().iter().map(|p| p["id"].as_i64().unwrap()).collect::<Vec<_>>(), vec![9, 8, 7]);
+	}
+}
@@ -0,0 +1,52 @@
+/// Offset basis and prime of the 64-bit FNV-1a hash.
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Hashes a string with 64-bit FNV-1a.
+/// Unlike the standard library's hashers its output is specified, so generated values don't change between Rust releases.
+pub fn fnv1a(s: &str) -> u64 {
+	s.bytes().fold(FNV_OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+/// The splitmix64 step, which spreads every bit of its input over the whole output.
+pub fn splitmix64(x: u64) -> u64 {
+	let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+	z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+	z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+	z ^ (z >> 31)
+}
+
+/// The `n`th number of the splitmix64 sequence that starts at `seed`, for values made of several independent parts.
+pub fn nth(seed: usize, n: usize) -> usize {
+	splitmix64((seed as u64).wrapping_add((n as u64).wrapping_mul(0x9e3779b97f4a7c15))) as usize
+}
+
+/// Derives the key of something identified by `name`, like an id or a route parameter, from a seed.
+pub fn derive(seed: usize, name: &str) -> usize {
+	splitmix64(seed as u64 ^ fnv1a(name)) as usize
+}
+
+/// Derives the key of something without a name from a seed.
+pub fn scramble(seed: usize) -> usize {
+	splitmix64(seed as u64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_golden_hashes() {
+		assert_eq!(fnv1a(""), 0xcbf29ce484222325);
+		assert_eq!(fnv1a("a"), 0xaf63dc4c8601ec8c);
+		assert_eq!(fnv1a("foobar"), 0x85944171f73967e8);
+
+		assert_eq!(splitmix64(0), 0xe220a8397b1dcdaf);
+		assert_eq!(splitmix64(0x9e3779b97f4a7c15), 0x6e789e6aa1b965f4);
+
+		assert_eq!(scramble(0), 0xe220a8397b1dcdaf);
+		assert_eq!(derive(0, ""), splitmix64(0xcbf29ce484222325) as usize);
+		assert_ne!(derive(0, "1"), derive(0, "2"));
+		assert_ne!(derive(0, "1"), derive(1, "1"));
+	}
+}
@@ -1,58 +1,142 @@
 use std::collections::HashMap;
 
-use crate::{schemas::ingest_schema, values::{DataTypes, ObjectExpressions},};
+use crate::{parse_template, query::{ingest_query, Query}, schemas::{ingest_lengths, ingest_schema}, values::{DataTypes, Lengths, ObjectExpressions, StringExpressions},};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Methods {
+	Get,
+	Post,
+	Put,
+	Patch,
+	Delete,
+}
+
+impl Methods {
+	pub fn parse(method: &str) -> Option<Methods> {
+		match method.to_ascii_uppercase().as_str() {
+			"GET" => Some(Methods::Get),
+			"POST" => Some(Methods::Post),
+			"PUT" => Some(Methods::Put),
+			"PATCH" => Some(Methods::Patch),
+			"DELETE" => Some(Methods::Delete),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+	pub body: DataTypes,
+	pub status: u16,
+	/// Header names and the templates that produce their values.
+	pub headers: Vec<(String, DataTypes)>,
+	/// Whether array responses can be paginated, sorted and filtered with query parameters.
+	pub query: Option<Query>,
+}
 
 #[derive(Debug, Clone)]
 pub struct Route {
-	name: String,
-	pub response: DataTypes,
+	pub name: String,
+	pub methods: HashMap<Methods, Response>,
+	/// Whether the route's collection is kept in memory and served with CRUD semantics.
+	pub store: bool,
 }
 
-pub fn ingest_routes(value: &serde_json::Value) -> HashMap<String, Route> {
-	fn ingest_routes_internal(value: &serde_json::Value, routes: &mut HashMap<String, Route>, parent: String) {
-		if let Some(jroutes) = value.get("routes") {
-			for (route_name, route) in jroutes.as_object().unwrap() {
-				let response = match route.get("response") {
-					Some(serde_json::Value::String(response)) => {
-						if response.contains("[]") {
-							let response = response.replace("[]", "");
-							DataTypes::Array(ObjectExpressions::Schema(response))
+fn ingest_response(response: Option<&serde_json::Value>) -> Response {
+	let status = match response.and_then(|response| response.get("status")) {
+		Some(serde_json::Value::Number(status)) => status.as_u64().and_then(|status| u16::try_from(status).ok()).unwrap_or(200),
+		_ => 200,
+	};
+
+	let mut headers = Vec::new();
+
+	if let Some(serde_json::Value::Object(jheaders)) = response.and_then(|response| response.get("headers")) {
+		for (name, value) in jheaders {
+			let template = match value {
+				serde_json::Value::String(value) => parse_template(value),
+				value => vec![StringExpressions::Literal(value.to_string())],
+			};
+
+			headers.push((name.to_string(), DataTypes::String(template)));
+		}
+	}
+
+	Response {
+		body: ingest_body(response),
+		status,
+		headers,
+		query: response.and_then(|response| response.get("query")).and_then(ingest_query),
+	}
+}
+
+fn ingest_body(response: Option<&serde_json::Value>) -> DataTypes {
+	match response {
+		Some(serde_json::Value::String(response)) => {
+			if response.contains("[]") {
+				let response = response.replace("[]", "");
+				DataTypes::Array(Box::new(DataTypes::Object(ObjectExpressions::Schema(response))), Lengths::Scale)
+			} else {
+				DataTypes::Object(ObjectExpressions::Schema(response.clone()))
+			}
+		},
+		Some(serde_json::Value::Object(response)) => {
+			match response.get("schema") {
+				Some(serde_json::Value::String(schema)) => {
+					if schema.contains("[]") {
+						let schema = schema.replace("[]", "");
+						DataTypes::Array(Box::new(DataTypes::Object(ObjectExpressions::Schema(schema))), ingest_lengths(response))
+					} else {
+						DataTypes::Object(ObjectExpressions::Schema(schema.clone()))
+					}
+				}
+				Some(serde_json::Value::Object(schema)) => {
+					if let Some(serde_json::Value::Object(items)) = schema.get("items") {
+						if let Some(serde_json::Value::Object(schema)) = items.get("schema") {
+							DataTypes::Array(Box::new(DataTypes::Object(ObjectExpressions::Object(ingest_schema(schema)))), ingest_lengths(response))
 						} else {
-							DataTypes::Object(ObjectExpressions::Schema(response.clone()))
-						}
-					},
-					Some(serde_json::Value::Object(response)) => {
-						match response.get("schema") {
-							Some(serde_json::Value::String(response)) => {
-								if response.contains("[]") {
-									let response = response.replace("[]", "");
-									DataTypes::Array(ObjectExpressions::Schema(response))
-								} else {
-									DataTypes::Object(ObjectExpressions::Schema(response.clone()))
-								}
-							}
-							Some(serde_json::Value::Object(schema)) => {
-								if let Some(serde_json::Value::Object(items)) = schema.get("items") {
-									if let Some(serde_json::Value::Object(schema)) = items.get("schema") {
-										DataTypes::Array(ObjectExpressions::Object(ingest_schema(schema)))
-									} else {
-										DataTypes::Null
-									}
-								} else {
-									DataTypes::Object(ObjectExpressions::Object(ingest_schema(schema)))
-								}
-							}
-							_ => DataTypes::Null,
+							DataTypes::Null
 						}
-					},
-					_ => DataTypes::Null,
+					} else {
+						DataTypes::Object(ObjectExpressions::Object(ingest_schema(schema)))
+					}
+				}
+				_ => DataTypes::Null,
+			}
+		},
+		_ => DataTypes::Null,
+	}
+}
+
+pub fn ingest_routes(value: &serde_json::Value) -> HashMap<String, Route> {
+	fn ingest_routes_internal(value: &serde_json::Value, routes: &mut HashMap<String, Route>, parent: String) {
+		if let Some(serde_json::Value::Object(jroutes)) = value.get("routes") {
+			for (route_name, route) in jroutes {
+				let mut methods = HashMap::new();
+
+				// The route level `response` is served under `method`, or GET if none was given
+				let method = match route.get("method") {
+					Some(serde_json::Value::String(method)) => Methods::parse(method).unwrap_or(Methods::Get),
+					_ => Methods::Get,
 				};
 
+				if route.get("response").is_some() || route.get("methods").is_none() {
+					methods.insert(method, ingest_response(route.get("response")));
+				}
+
+				if let Some(serde_json::Value::Object(jmethods)) = route.get("methods") {
+					for (method_name, definition) in jmethods {
+						if let Some(method) = Methods::parse(method_name) {
+							methods.insert(method, ingest_response(definition.get("response")));
+						}
+					}
+				}
+
 				let r = format!("{}{}", parent, route_name);
 
 				routes.insert(r.clone(), Route {
 					name: r.clone(),
-					response,
+					methods,
+					store: matches!(route.get("store"), Some(serde_json::Value::Bool(true))),
 				});
 
 				ingest_routes_internal(route, routes, r);
@@ -69,7 +153,7 @@ pub fn ingest_routes(value: &serde_json::Value) -> HashMap<String, Route> {
 
 #[cfg(test)]
 mod tests {
-	use crate::{schemas::Field, values::StringExpressions};
+	use crate::{query::Styles, schemas::Field};
 
 	use super::*;
 
@@ -106,20 +190,158 @@ mod tests {
 
 		{
 			let people = routes.get("/people").unwrap();
-			assert_eq!(people.response, DataTypes::Array(ObjectExpressions::Schema("Person".to_string())));
+			assert_eq!(people.methods[&Methods::Get].body, DataTypes::Array(Box::new(DataTypes::Object(ObjectExpressions::Schema("Person".to_string()))), Lengths::Scale));
 
 			{
 				let people_id = routes.get("/people/:id").unwrap();
-				assert_eq!(people_id.response, DataTypes::Object(ObjectExpressions::Schema("Person".to_string())));
+				assert_eq!(people_id.methods[&Methods::Get].body, DataTypes::Object(ObjectExpressions::Schema("Person".to_string())));
 			}
 
 			{
 				let people_positions = routes.get("/people/positions").unwrap();
-				assert_eq!(people_positions.response, DataTypes::Array(ObjectExpressions::Object(vec![Field {
+				assert_eq!(people_positions.methods[&Methods::Get].body, DataTypes::Array(Box::new(DataTypes::Object(ObjectExpressions::Object(vec![Field {
 					name: "name".to_string(),
 					datatype: DataTypes::String(vec![StringExpressions::Literal("Teller".to_string())]),
-				}])));
+				}]))), Lengths::Scale));
+			}
+		}
+	}
+
+	#[test]
+	fn test_ingest_route_lengths() {
+		const STRING: &str = r#"
+		{
+			routes: {
+				"/people": {
+					response: { schema: "Person[]", count: 3 },
+					routes: {
+						"/positions": {
+							response: { schema: { items: { schema: { fields: { name: { template: "Teller" } } } } }, minItems: 2, maxItems: 5 },
+						},
+					},
+				},
+			}
+		}
+		"#;
+
+		let values: serde_json::Value = serde_json5::from_str(STRING).unwrap();
+
+		let routes = ingest_routes(&values);
+
+		{
+			let people = routes.get("/people").unwrap();
+			assert_eq!(people.methods[&Methods::Get].body, DataTypes::Array(Box::new(DataTypes::Object(ObjectExpressions::Schema("Person".to_string()))), Lengths::Count(3)));
+		}
+
+		{
+			let people_positions = routes.get("/people/positions").unwrap();
+			assert_eq!(people_positions.methods[&Methods::Get].body, DataTypes::Array(Box::new(DataTypes::Object(ObjectExpressions::Object(vec![Field {
+				name: "name".to_string(),
+				datatype: DataTypes::String(vec![StringExpressions::Literal("Teller".to_string())]),
+			}]))), Lengths::Range(Some(2), Some(5))));
+		}
+	}
+
+	#[test]
+	fn test_ingest_route_methods() {
+		const STRING: &str = r#"
+		{
+			routes: {
+				"/people": {
+					response: { schema: "Person[]", },
+					store: true,
+					methods: {
+						post: { response: { schema: "Person", }, },
+					},
+					routes: {
+						"/:id": {
+							methods: {
+								put: { response: { schema: "Person", }, },
+								DELETE: { },
+							},
+						},
+						"/import": {
+							method: "post",
+							response: { schema: "Person[]", },
+						},
+					},
+				},
 			}
 		}
+		"#;
+
+		let values: serde_json::Value = serde_json5::from_str(STRING).unwrap();
+
+		let routes = ingest_routes(&values);
+
+		{
+			let people = routes.get("/people").unwrap();
+			assert!(people.store);
+			assert_eq!(people.methods.len(), 2);
+			assert_eq!(people.methods[&Methods::Get].body, DataTypes::Array(Box::new(DataTypes::Object(ObjectExpressions::Schema("Person".to_string()))), Lengths::Scale));
+			assert_eq!(people.methods[&Methods::Post].body, DataTypes::Object(ObjectExpressions::Schema("Person".to_string())));
+		}
+
+		{
+			let people_id = routes.get("/people/:id").unwrap();
+			assert!(!people_id.store);
+			assert_eq!(people_id.methods.len(), 2);
+			assert_eq!(people_id.methods[&Methods::Put].body, DataTypes::Object(ObjectExpressions::Schema("Person".to_string())));
+			assert_eq!(people_id.methods[&Methods::Delete].body, DataTypes::Null);
+		}
+
+		{
+			let people_import = routes.get("/people/import").unwrap();
+			assert_eq!(people_import.methods.len(), 1);
+			assert_eq!(people_import.methods[&Methods::Post].body, DataTypes::Array(Box::new(DataTypes::Object(ObjectExpressions::Schema("Person".to_string()))), Lengths::Scale));
+		}
+	}
+
+	#[test]
+	fn test_ingest_route_status_and_headers() {
+		const STRING: &str = r#"
+		{
+			routes: {
+				"/people": {
+					response: { schema: "Person[]", headers: { "X-Total-Count": 16 }, query: { style: "envelope", pageSize: 10 } },
+					methods: {
+						post: { response: { schema: "Person", status: 201, headers: { Location: "/people/${this.id}" } } },
+					},
+					routes: {
+						"/:id": {
+							methods: {
+								delete: { response: { status: 204 } },
+							},
+						},
+					},
+				},
+			}
+		}
+		"#;
+
+		let values: serde_json::Value = serde_json5::from_str(STRING).unwrap();
+
+		let routes = ingest_routes(&values);
+
+		{
+			let people = routes.get("/people").unwrap();
+			assert_eq!(people.methods[&Methods::Get], Response {
+				body: DataTypes::Array(Box::new(DataTypes::Object(ObjectExpressions::Schema("Person".to_string()))), Lengths::Scale),
+				status: 200,
+				headers: vec![("X-Total-Count".to_string(), DataTypes::String(vec![StringExpressions::Literal("16".to_string())]))],
+				query: Some(Query { style: Styles::Envelope, page_size: Some(10) }),
+			});
+			assert_eq!(people.methods[&Methods::Post], Response {
+				body: DataTypes::Object(ObjectExpressions::Schema("Person".to_string())),
+				status: 201,
+				headers: vec![("Location".to_string(), DataTypes::String(vec![StringExpressions::Literal("/people/".to_string()), StringExpressions::Variable("this.id".to_string())]))],
+				query: None,
+			});
+		}
+
+		{
+			let people_id = routes.get("/people/:id").unwrap();
+			assert_eq!(people_id.methods[&Methods::Delete], Response { body: DataTypes::Null, status: 204, headers: vec![], query: None });
+		}
 	}
 }
@@ -1,11 +1,13 @@
 use std::collections::HashMap;
 
-use crate::{parse_template, values::{DataTypes, Dates, NumberExpressions, ObjectExpressions, StringExpressions}};
+use crate::{parse_template, values::{BooleanExpressions, DataTypes, Dates, Lengths, NumberExpressions, ObjectExpressions, StringExpressions, CHANCE_SCALE}};
 
 #[derive(Debug, Clone)]
 pub struct Schema {
 	pub name: String,
 	pub fields: Vec<Field>,
+	/// Length of the route that serves this schema's collection, if there is one.
+	pub collection: Option<Lengths>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -18,98 +20,259 @@ pub fn ingest_schemas(source: &serde_json::Value) -> HashMap<String, Schema> {
 	let mut schemas = HashMap::new();
 
 	if let Some(serde_json::Value::Object(jschemas)) = source.get("schemas") {
-    	for (name, schema) in jschemas {
-     		if let serde_json::Value::Object(schema) = schema {
-     			let fields = ingest_schema(schema);
-
+		for (name, schema) in jschemas {
+			if let serde_json::Value::Object(_) = schema {
 				schemas.insert(name.to_string(), Schema {
 					name: name.to_string(),
-					fields,
+					fields: resolve_fields(name, jschemas, &mut Vec::new()),
+					collection: None,
 				});
-       		}
-     	}
-    }
+			}
+		}
+	}
 
-    schemas
+	schemas
 }
 
-pub fn ingest_schema(source: &serde_json::Map<String, serde_json::Value>) -> Vec<Field> {
-	let jfields = source.get("fields").unwrap().as_object().unwrap();
+/// The schemas a schema inherits fields from, in order: the ones it `extends` and then its `allOf` mixins.
+pub fn parents(schema: &serde_json::Map<String, serde_json::Value>) -> Vec<&str> {
+	["extends", "allOf"].iter().flat_map(|key| match schema.get(*key) {
+		Some(serde_json::Value::String(parent)) => vec![parent.as_str()],
+		Some(serde_json::Value::Array(parents)) => parents.iter().filter_map(|parent| parent.as_str()).collect(),
+		_ => Vec::new(),
+	}).collect()
+}
+
+/// Reads the fields of a schema along with the ones it inherits.
+/// Inherited fields come first, and a field defined again, by a later parent or the schema itself, overrides the earlier one in place.
+fn resolve_fields<'a>(name: &'a str, jschemas: &'a serde_json::Map<String, serde_json::Value>, trail: &mut Vec<&'a str>) -> Vec<Field> {
+	let Some(serde_json::Value::Object(schema)) = jschemas.get(name) else {
+		return Vec::new();
+	};
+
+	// Validation reports inheritance cycles, here the schema closing one is just skipped
+	if trail.contains(&name) {
+		return Vec::new();
+	}
+
+	trail.push(name);
 
 	let mut fields = Vec::new();
 
-	for (field_name, field) in jfields {
-		if let serde_json::Value::Object(field) = field {
-			if let Some(serde_json::Value::String(template)) = field.get("template") {
-				fields.push(Field {
-					name: field_name.to_string(),
-					datatype: DataTypes::String(parse_template(template.as_str())),
-				});
-			}
+	for parent in parents(schema) {
+		override_fields(&mut fields, resolve_fields(parent, jschemas, trail));
+	}
 
-			if let Some(serde_json::Value::Object(range)) = field.get("range") {
-				if let (Some(serde_json::Value::Number(min)), Some(serde_json::Value::Number(max))) = (range.get("min"), range.get("max")) {
-					fields.push(Field {
-						name: field_name.to_string(),
-						datatype: DataTypes::Number(NumberExpressions::Range(min.as_i64().unwrap(), max.as_i64().unwrap())),
-					});
-				}
-			}
+	override_fields(&mut fields, ingest_schema(schema));
 
-			if let Some(serde_json::Value::Object(date)) = field.get("date") {
-				let frame = if let Some(serde_json::Value::String(frame)) = date.get("frame") {
-					frame.as_str()
-				} else {
-					"now"
-				};
-
-				let frame = match frame {
-					"now" => Dates::Now,
-					"future" => Dates::Future,
-					"soon" => Dates::Soon,
-					"recent" => Dates::Recent,
-					"past" => Dates::Past,
-					_ => Dates::Now,
-				};
-
-				fields.push(Field {
-					name: field_name.to_string(),
-					datatype: DataTypes::String(vec![StringExpressions::Date(frame)]),
-				});
-			};
-
-			if let Some(serde_json::Value::Object(items)) = field.get("items") {
-				if let Some(serde_json::Value::String(schema)) = items.get("schema") {
-					fields.push(Field {
-						name: field_name.to_string(),
-						datatype: DataTypes::Array(ObjectExpressions::Schema(schema.to_string())),
-					});
-				}
-			}
+	trail.pop();
 
-			if let Some(serde_json::Value::Object(_)) = field.get("fields") {
-				let sub_fields = ingest_schema(field);
-				fields.push(Field {
-					name: field_name.to_string(),
-					datatype: DataTypes::Object(ObjectExpressions::Object(sub_fields)),
-				});
-			}
+	fields
+}
 
-			if let Some(serde_json::Value::Array(values)) = field.get("enum") {
-				let mut enum_values = Vec::new();
+fn override_fields(fields: &mut Vec<Field>, overrides: Vec<Field>) {
+	for field in overrides {
+		match fields.iter_mut().find(|existing| existing.name == field.name) {
+			Some(existing) => *existing = field,
+			None => fields.push(field),
+		}
+	}
+}
 
-				for value in values {
-					if let serde_json::Value::String(value) = value {
-						enum_values.push(value.to_string());
-					}
-				}
+/// Reads the `count`, `minItems` and `maxItems` keys that set the length of an array.
+/// Most items an array can have, so generating one doesn't exhaust memory.
+pub const MAX_LENGTH: u64 = 1_000_000;
 
-				fields.push(Field {
-					name: field_name.to_string(),
-					datatype: DataTypes::Enum(enum_values),
-				});
+pub fn ingest_lengths(source: &serde_json::Map<String, serde_json::Value>) -> Lengths {
+	let get = |key: &str| source.get(key).and_then(|value| value.as_u64()).map(|value| value as usize);
+
+	match (get("count"), get("minItems"), get("maxItems")) {
+		(Some(count), _, _) => Lengths::Count(count),
+		(None, None, None) => Lengths::Scale,
+		(None, min, max) => Lengths::Range(min, max),
+	}
+}
+
+/// Decimals of ranges with non-integer bounds and no `decimals` key.
+const DEFAULT_DECIMALS: u64 = 2;
+/// Most decimals a range can have, so its bounds still fit in integers.
+pub const MAX_DECIMALS: u64 = 9;
+
+/// Reads a range, either `{ min, max, decimals }` or `[min, max]`.
+/// Ranges with `decimals`, or with non-integer bounds, produce decimal numbers.
+pub fn ingest_range(source: &serde_json::Value) -> Option<NumberExpressions> {
+	let (min, max, decimals) = match source {
+		serde_json::Value::Object(range) => (range.get("min")?, range.get("max")?, range.get("decimals").and_then(|decimals| decimals.as_u64())),
+		serde_json::Value::Array(range) if range.len() == 2 => (&range[0], &range[1], None),
+		_ => return None,
+	};
+
+	match (min.as_i64(), max.as_i64(), decimals) {
+		(Some(min), Some(max), None) => Some(NumberExpressions::Range(min, max)),
+		(_, _, decimals) => {
+			let decimals = decimals.unwrap_or(DEFAULT_DECIMALS).min(MAX_DECIMALS) as u32;
+			let units = |bound: &serde_json::Value| bound.as_f64().map(|bound| (bound * 10f64.powi(decimals as i32)).round() as i64);
+
+			Some(NumberExpressions::Decimal(units(min)?, units(max)?, decimals))
+		},
+	}
+}
+
+/// Reads a probability, from 0 to 1, as a chance out of `CHANCE_SCALE`.
+fn ingest_chance(source: Option<&serde_json::Value>) -> Option<u32> {
+	let probability = source?.as_f64()?.clamp(0.0, 1.0);
+
+	Some((probability * CHANCE_SCALE as f64).round() as u32)
+}
+
+/// Reads a `bool` generator, true with its `probability`, or half of the time.
+pub fn ingest_bool(source: &serde_json::Map<String, serde_json::Value>) -> BooleanExpressions {
+	BooleanExpressions::Chance(ingest_chance(source.get("probability")).unwrap_or(CHANCE_SCALE as u32 / 2))
+}
+
+/// Reads a field that isn't a definition object, which always produces the same value.
+/// Objects inside literal arrays are literals as well.
+pub fn ingest_literal(source: &serde_json::Value) -> DataTypes {
+	match source {
+		serde_json::Value::String(value) => DataTypes::String(vec![StringExpressions::Literal(value.to_string())]),
+		serde_json::Value::Number(value) => DataTypes::Number(NumberExpressions::Literal(value.clone())),
+		serde_json::Value::Bool(value) => DataTypes::Boolean(BooleanExpressions::Literal(*value)),
+		serde_json::Value::Null => DataTypes::Null,
+		serde_json::Value::Array(values) => DataTypes::List(values.iter().map(ingest_literal).collect()),
+		serde_json::Value::Object(values) => DataTypes::Object(ObjectExpressions::Object(values.iter().map(|(name, value)| Field {
+			name: name.to_string(),
+			datatype: ingest_literal(value),
+		}).collect())),
+	}
+}
+
+/// Splits a weighted enum value, an object with a `value` and an optional `weight` and nothing else, into both.
+/// Any other object is a value itself.
+pub fn weighted_value(value: &serde_json::Value) -> Option<(&serde_json::Value, Option<&serde_json::Value>)> {
+	let serde_json::Value::Object(weighted) = value else {
+		return None;
+	};
+
+	if !weighted.contains_key("value") || weighted.keys().any(|key| key != "value" && key != "weight") {
+		return None;
+	}
+
+	Some((&weighted["value"], weighted.get("weight")))
+}
+
+/// The keys that make an object a field definition rather than a literal.
+pub const GENERATORS: [&str; 10] = ["template", "range", "bool", "date", "schema", "items", "fields", "ref", "enum", "values"];
+
+/// Reads a field definition, or a literal, into the data type it generates.
+/// Definitions are objects with a generator key, like `template` or `range`, and the last one wins if there are several.
+pub fn ingest_field(source: &serde_json::Value) -> Option<DataTypes> {
+	let serde_json::Value::Object(field) = source else {
+		return Some(ingest_literal(source));
+	};
+
+	let mut datatype = None;
+
+	if let Some(serde_json::Value::String(template)) = field.get("template") {
+		datatype = Some(DataTypes::String(parse_template(template.as_str())));
+	}
+
+	if let Some(range) = field.get("range") {
+		if let Some(range) = ingest_range(range) {
+			datatype = Some(DataTypes::Number(range));
+		}
+	}
+
+	if let Some(serde_json::Value::Object(bool)) = field.get("bool") {
+		datatype = Some(DataTypes::Boolean(ingest_bool(bool)));
+	}
+
+	if let Some(serde_json::Value::Object(date)) = field.get("date") {
+		let frame = if let Some(serde_json::Value::String(frame)) = date.get("frame") {
+			frame.as_str()
+		} else {
+			"now"
+		};
+
+		let frame = match frame {
+			"now" => Dates::Now,
+			"future" => Dates::Future,
+			"soon" => Dates::Soon,
+			"recent" => Dates::Recent,
+			"past" => Dates::Past,
+			_ => Dates::Now,
+		};
+
+		datatype = Some(DataTypes::String(vec![StringExpressions::Date(frame)]));
+	};
+
+	if let Some(serde_json::Value::String(schema)) = field.get("schema") {
+		datatype = Some(DataTypes::Object(ObjectExpressions::Schema(schema.to_string())));
+	}
+
+	// Items are field definitions themselves, so arrays can hold anything a field can
+	if let Some(items) = field.get("items") {
+		if let Some(item) = ingest_field(items) {
+			datatype = Some(DataTypes::Array(Box::new(item), ingest_lengths(field)));
+		}
+	}
+
+	if let Some(serde_json::Value::Object(_)) = field.get("fields") {
+		datatype = Some(DataTypes::Object(ObjectExpressions::Object(ingest_schema(field))));
+	}
+
+	if let Some(serde_json::Value::String(reference)) = field.get("ref") {
+		let (schema, field) = match reference.split_once('.') {
+			Some((schema, field)) => (schema.to_string(), Some(field.to_string())),
+			None => (reference.to_string(), None),
+		};
+
+		datatype = Some(DataTypes::Reference(schema, field));
+	}
+
+	if let Some(serde_json::Value::Array(values)) = field.get("enum").or_else(|| field.get("values")) {
+		let mut enum_values = Vec::new();
+
+		// Values are of any type, or weighted like `{ value: "active", weight: 80 }`
+		for value in values {
+			match weighted_value(value) {
+				Some((value, weight)) => {
+					let weight = weight.and_then(|weight| weight.as_u64()).map_or(1, |weight| weight.min(u32::MAX as u64) as u32);
+					enum_values.push((value.clone(), weight));
+				},
+				None => enum_values.push((value.clone(), 1)),
 			}
 		}
+
+		datatype = Some(DataTypes::Enum(enum_values));
+	}
+
+	let mut datatype = datatype?;
+
+	if let Some(chance) = ingest_chance(field.get("nullable")) {
+		datatype = DataTypes::Nullable(chance, Box::new(datatype));
+	}
+
+	if let Some(chance) = ingest_chance(field.get("optional")) {
+		datatype = DataTypes::Optional(chance, Box::new(datatype));
+	}
+
+	Some(datatype)
+}
+
+pub fn ingest_schema(source: &serde_json::Map<String, serde_json::Value>) -> Vec<Field> {
+	let Some(serde_json::Value::Object(jfields)) = source.get("fields") else {
+		return Vec::new();
+	};
+
+	let mut fields = Vec::new();
+
+	for (field_name, field) in jfields {
+		if let Some(datatype) = ingest_field(field) {
+			fields.push(Field {
+				name: field_name.to_string(),
+				datatype,
+			});
+		}
 	}
 
 	fields
@@ -136,8 +299,27 @@ mod tests {
 							id: { template: "${this.id::UUID}", },
 							name: { template: "${FULL_NAME}" },
 							risk: { range: { min: 1, max: 100, }, },
+							level: { range: [1, 3] },
+							weight: { range: [40.5, 120] },
+							rating: { range: { min: 0.5, max: 5, decimals: 1 } },
+							balance: { range: { min: -10.25, max: 99.99 } },
+							verified: { bool: { probability: 0.25 } },
+							subscribed: { bool: {} },
+							nickname: { template: "${FIRST_NAME}", optional: 0.3, nullable: 0.1 },
 							fields: { items: { schema: "Field" } },
+							contacts: { items: { schema: "Field" }, minItems: 1, maxItems: 4 },
+							extra: { items: { schema: "Field" }, count: 2 },
+							tags: { items: { template: "tag-${NOUN}" }, count: 3 },
+							scores: { items: { range: { min: 1, max: 5 } } },
+							primary: { schema: "Field" },
 							title: { enum: ["Mr", "Mrs", "Ms", "Dr"] },
+							status: { values: [{ value: "active", weight: 80 }, "pending", { value: "banned", weight: 2 }] },
+							tier: { enum: [1, 2.5, { value: 3, weight: 4 }] },
+							flags: { enum: [true, null, ["a", "b"]] },
+							plan: { enum: [{ name: "Free", price: 0 }, { value: { value: "Pro" }, weight: 2 }] },
+							size: { enum: [{ name: "Small", weight: 2 }, { value: "Large", weight: 3 }] },
+							managerId: { ref: "Person.id" },
+							campaign: { ref: "Campaign" },
 						},
 					},
 					Campaign: {
@@ -146,6 +328,12 @@ mod tests {
 							name: { template: "My ${ADJECTIVE} campaign", },
 							start: { date: { frame: "recent" } },
 							end: { date: { frame: "future" } },
+							status: "draft",
+							priority: 3,
+							budget: 1500.5,
+							active: true,
+							archivedAt: null,
+							tags: ["mocks", 2, false],
 							stats: {
 								fields: {
 									batch: { range: { min: 1, max: 10 } },
@@ -186,11 +374,69 @@ mod tests {
 				let risk_field = person.fields.iter().find(|f| f.name == "risk").unwrap();
 				assert_eq!(risk_field.datatype, DataTypes::Number(NumberExpressions::Range(1, 100)));
 
+				let level_field = person.fields.iter().find(|f| f.name == "level").unwrap();
+				assert_eq!(level_field.datatype, DataTypes::Number(NumberExpressions::Range(1, 3)));
+
+				let weight_field = person.fields.iter().find(|f| f.name == "weight").unwrap();
+				assert_eq!(weight_field.datatype, DataTypes::Number(NumberExpressions::Decimal(4050, 12000, 2)));
+
+				let rating_field = person.fields.iter().find(|f| f.name == "rating").unwrap();
+				assert_eq!(rating_field.datatype, DataTypes::Number(NumberExpressions::Decimal(5, 50, 1)));
+
+				let balance_field = person.fields.iter().find(|f| f.name == "balance").unwrap();
+				assert_eq!(balance_field.datatype, DataTypes::Number(NumberExpressions::Decimal(-1025, 9999, 2)));
+
+				let verified_field = person.fields.iter().find(|f| f.name == "verified").unwrap();
+				assert_eq!(verified_field.datatype, DataTypes::Boolean(BooleanExpressions::Chance(250_000)));
+
+				let subscribed_field = person.fields.iter().find(|f| f.name == "subscribed").unwrap();
+				assert_eq!(subscribed_field.datatype, DataTypes::Boolean(BooleanExpressions::Chance(500_000)));
+
+				let nickname_field = person.fields.iter().find(|f| f.name == "nickname").unwrap();
+				assert_eq!(nickname_field.datatype, DataTypes::Optional(300_000, Box::new(DataTypes::Nullable(100_000, Box::new(DataTypes::String(vec![StringExpressions::Variable("FIRST_NAME".to_string())]))))));
+
 				let fields_field = person.fields.iter().find(|f| f.name == "fields").unwrap();
-				assert_eq!(fields_field.datatype, DataTypes::Array(ObjectExpressions::Schema("Field".to_string())));
+				assert_eq!(fields_field.datatype, DataTypes::Array(Box::new(DataTypes::Object(ObjectExpressions::Schema("Field".to_string()))), Lengths::Scale));
+
+				let contacts_field = person.fields.iter().find(|f| f.name == "contacts").unwrap();
+				assert_eq!(contacts_field.datatype, DataTypes::Array(Box::new(DataTypes::Object(ObjectExpressions::Schema("Field".to_string()))), Lengths::Range(Some(1), Some(4))));
+
+				let extra_field = person.fields.iter().find(|f| f.name == "extra").unwrap();
+				assert_eq!(extra_field.datatype, DataTypes::Array(Box::new(DataTypes::Object(ObjectExpressions::Schema("Field".to_string()))), Lengths::Count(2)));
+
+				let tags_field = person.fields.iter().find(|f| f.name == "tags").unwrap();
+				assert_eq!(tags_field.datatype, DataTypes::Array(Box::new(DataTypes::String(vec![StringExpressions::Literal("tag-".to_string()), StringExpressions::Variable("NOUN".to_string())])), Lengths::Count(3)));
+
+				let scores_field = person.fields.iter().find(|f| f.name == "scores").unwrap();
+				assert_eq!(scores_field.datatype, DataTypes::Array(Box::new(DataTypes::Number(NumberExpressions::Range(1, 5))), Lengths::Scale));
+
+				let primary_field = person.fields.iter().find(|f| f.name == "primary").unwrap();
+				assert_eq!(primary_field.datatype, DataTypes::Object(ObjectExpressions::Schema("Field".to_string())));
+
+				let manager_id_field = person.fields.iter().find(|f| f.name == "managerId").unwrap();
+				assert_eq!(manager_id_field.datatype, DataTypes::Reference("Person".to_string(), Some("id".to_string())));
+
+				let campaign_field = person.fields.iter().find(|f| f.name == "campaign").unwrap();
+				assert_eq!(campaign_field.datatype, DataTypes::Reference("Campaign".to_string(), None));
 
 				let title_field = person.fields.iter().find(|f| f.name == "title").unwrap();
-				assert_eq!(title_field.datatype, DataTypes::Enum(vec!["Mr".to_string(), "Mrs".to_string(), "Ms".to_string(), "Dr".to_string()]));
+				assert_eq!(title_field.datatype, DataTypes::Enum(vec![("Mr".into(), 1), ("Mrs".into(), 1), ("Ms".into(), 1), ("Dr".into(), 1)]));
+
+				let status_field = person.fields.iter().find(|f| f.name == "status").unwrap();
+				assert_eq!(status_field.datatype, DataTypes::Enum(vec![("active".into(), 80), ("pending".into(), 1), ("banned".into(), 2)]));
+
+				let tier_field = person.fields.iter().find(|f| f.name == "tier").unwrap();
+				assert_eq!(tier_field.datatype, DataTypes::Enum(vec![(1.into(), 1), (2.5.into(), 1), (3.into(), 4)]));
+
+				let flags_field = person.fields.iter().find(|f| f.name == "flags").unwrap();
+				assert_eq!(flags_field.datatype, DataTypes::Enum(vec![(true.into(), 1), (serde_json::Value::Null, 1), (serde_json::json!(["a", "b"]), 1)]));
+
+				let plan_field = person.fields.iter().find(|f| f.name == "plan").unwrap();
+				assert_eq!(plan_field.datatype, DataTypes::Enum(vec![(serde_json::json!({ "name": "Free", "price": 0 }), 1), (serde_json::json!({ "value": "Pro" }), 2)]));
+
+				// Objects with keys other than `value` and `weight` are literals, even with a `weight`
+				let size_field = person.fields.iter().find(|f| f.name == "size").unwrap();
+				assert_eq!(size_field.datatype, DataTypes::Enum(vec![(serde_json::json!({ "name": "Small", "weight": 2 }), 1), ("Large".into(), 3)]));
 			}
 
 			{
@@ -214,7 +460,88 @@ mod tests {
 					Field{ name: "commited".to_string(), datatype: DataTypes::Number(NumberExpressions::Range(1, 10)) },
 						Field{ name: "total".to_string(), datatype: DataTypes::Number(NumberExpressions::Range(1, 10)) },
 				])));
+
+				let status_field = campaign.fields.iter().find(|f| f.name == "status").unwrap();
+				assert_eq!(status_field.datatype, DataTypes::String(vec![StringExpressions::Literal("draft".to_string())]));
+
+				let priority_field = campaign.fields.iter().find(|f| f.name == "priority").unwrap();
+				assert_eq!(priority_field.datatype, DataTypes::Number(NumberExpressions::Literal(3.into())));
+
+				let budget_field = campaign.fields.iter().find(|f| f.name == "budget").unwrap();
+				assert_eq!(budget_field.datatype, DataTypes::Number(NumberExpressions::Literal(serde_json::Number::from_f64(1500.5).unwrap())));
+
+				let active_field = campaign.fields.iter().find(|f| f.name == "active").unwrap();
+				assert_eq!(active_field.datatype, DataTypes::Boolean(BooleanExpressions::Literal(true)));
+
+				let archived_at_field = campaign.fields.iter().find(|f| f.name == "archivedAt").unwrap();
+				assert_eq!(archived_at_field.datatype, DataTypes::Null);
+
+				let tags_field = campaign.fields.iter().find(|f| f.name == "tags").unwrap();
+				assert_eq!(tags_field.datatype, DataTypes::List(vec![
+					DataTypes::String(vec![StringExpressions::Literal("mocks".to_string())]),
+					DataTypes::Number(NumberExpressions::Literal(2.into())),
+					DataTypes::Boolean(BooleanExpressions::Literal(false)),
+				]));
 			}
 		}
 	}
+
+	#[test]
+	fn test_ingest_schemas_inheritance() {
+		let source = serde_json5::from_str::<serde_json::Value>(r#"{
+			schemas: {
+				BaseEntity: {
+					fields: {
+						id: { template: "${this.id::UUID}" },
+						createdAt: { date: { frame: "past" } },
+					},
+				},
+				Audited: {
+					fields: {
+						updatedAt: { date: { frame: "recent" } },
+						createdAt: { date: { frame: "recent" } },
+					},
+				},
+				User: {
+					extends: "BaseEntity",
+					allOf: ["Audited"],
+					fields: {
+						id: 7,
+						name: { template: "${FULL_NAME}" },
+					},
+				},
+				Admin: {
+					extends: "User",
+				},
+				Loop: {
+					extends: "Cycle",
+					fields: {
+						name: "loop",
+					},
+				},
+				Cycle: {
+					extends: "Loop",
+				},
+			},
+		}"#).unwrap();
+
+		let schemas = ingest_schemas(&source);
+
+		let fields = |name: &str| schemas[name].fields.iter().map(|field| (field.name.as_str(), field.datatype.clone())).collect::<Vec<_>>();
+
+		// Inherited fields come first and keep their place when overridden
+		let user = vec![
+			("createdAt", DataTypes::String(vec![StringExpressions::Date(Dates::Recent)])),
+			("id", DataTypes::Number(NumberExpressions::Literal(7.into()))),
+			("updatedAt", DataTypes::String(vec![StringExpressions::Date(Dates::Recent)])),
+			("name", DataTypes::String(vec![StringExpressions::Variable("FULL_NAME".to_string())])),
+		];
+
+		assert_eq!(fields("User"), user);
+		assert_eq!(fields("Admin"), user);
+
+		// Cycles are reported by validation, and don't keep schemas from being read
+		assert_eq!(fields("Loop"), vec![("name", DataTypes::String(vec![StringExpressions::Literal("loop".to_string())]))]);
+		assert_eq!(fields("Cycle"), fields("Loop"));
+	}
 }
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use crate::fake::{self, Locale};
+
+/// Settings of the configuration that apply to every generated value.
+#[derive(Debug, Clone)]
+pub struct Settings {
+	/// The locale of template variables that don't name their own.
+	pub locale: &'static Locale,
+	/// Word lists of the configuration, sampled by `${dict.<name>}`.
+	pub dictionaries: HashMap<String, Vec<String>>,
+}
+
+impl Default for Settings {
+	fn default() -> Settings {
+		Settings { locale: fake::default_locale(), dictionaries: HashMap::new() }
+	}
+}
+
+impl Settings {
+	/// Picks a word of the dictionary `name`, or returns `None` if there is no such dictionary.
+	pub fn dictionary_word(&self, name: &str, seed: usize) -> Option<&str> {
+		let words = self.dictionaries.get(name)?;
+
+		words.get(seed % words.len().max(1)).map(|word| word.as_str())
+	}
+}
+
+/// Reads the top-level `locale` and `dictionaries`.
+/// Dictionaries read from files have been replaced with their words when the configuration was read.
+pub fn ingest_settings(source: &serde_json::Value) -> Settings {
+	let locale = source.get("locale").and_then(|locale| locale.as_str()).and_then(fake::find_locale).unwrap_or(fake::default_locale());
+
+	let mut dictionaries = HashMap::new();
+
+	if let Some(serde_json::Value::Object(jdictionaries)) = source.get("dictionaries") {
+		for (name, words) in jdictionaries {
+			if let serde_json::Value::Array(words) = words {
+				dictionaries.insert(name.clone(), words.iter().filter_map(|word| word.as_str().map(|word| word.to_string())).collect());
+			}
+		}
+	}
+
+	Settings { locale, dictionaries }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_ingest_settings() {
+		let source: serde_json::Value = serde_json5::from_str(r#"{
+			locale: "es_AR",
+			dictionaries: {
+				branches: ["Palermo", "Belgrano", "Recoleta"],
+				empty: [],
+			},
+		}"#).unwrap();
+
+		let settings = ingest_settings(&source);
+
+		assert_eq!(settings.locale.name, "es_AR");
+		assert_eq!(settings.dictionary_word("branches", 4), Some("Belgrano"));
+		assert_eq!(settings.dictionary_word("empty", 4), None);
+		assert_eq!(settings.dictionary_word("skus", 4), None);
+
+		assert_eq!(ingest_settings(&serde_json::json!({})).locale.name, "en_US");
+	}
+}
@@ -0,0 +1,236 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{schemas::Schema, settings::Settings, values::{build_value, Context, DataTypes, Lengths}};
+
+/// In-memory collection backing a route declared with `store: true`.
+/// Items are seeded from the route's schema and are then read and written by the CRUD handlers.
+#[derive(Debug, Clone)]
+pub struct Store {
+	item: DataTypes,
+	items: Vec<(String, serde_json::Value)>,
+	next: usize,
+	seed: usize,
+	size: usize,
+}
+
+/// Why a store couldn't read or write an item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreError {
+	NotFound,
+	/// Another item already has the id.
+	DuplicateId(String),
+}
+
+impl fmt::Display for StoreError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			StoreError::NotFound => write!(f, "no item has that id"),
+			StoreError::DuplicateId(id) => write!(f, "more than one item has the id `{}`, ids must be unique", id),
+		}
+	}
+}
+
+/// Returns the key an item is stored under, its `id` field if it has one.
+fn item_key(item: &serde_json::Value) -> Option<String> {
+	match item.get("id") {
+		Some(serde_json::Value::String(id)) => Some(id.clone()),
+		Some(serde_json::Value::Number(id)) => Some(id.to_string()),
+		_ => None,
+	}
+}
+
+impl Store {
+	/// Generates the items of the store, which must all have different ids.
+	pub fn seed(schemas: &HashMap<String, Schema>, item: &DataTypes, length: &Lengths, ctx: &Context) -> Result<Store, StoreError> {
+		let mut store = Store { item: item.clone(), items: Vec::new(), next: 0, seed: ctx.seed, size: ctx.size };
+
+		if let serde_json::Value::Array(items) = build_value(schemas, &DataTypes::Array(Box::new(item.clone()), length.clone()), ctx) {
+			for item in items {
+				let key = item_key(&item).unwrap_or_else(|| store.next.to_string());
+				store.check_key(&key, None)?;
+				store.items.push((key, item));
+				store.next += 1;
+			}
+		}
+
+		Ok(store)
+	}
+
+	/// Generates the items again from a new definition, like the one of a reloaded configuration, dropping any changes made to them.
+	pub fn reset(&mut self, schemas: &HashMap<String, Schema>, item: &DataTypes, length: &Lengths, settings: &Settings) -> Result<(), StoreError> {
+		*self = Store::seed(schemas, item, length, &Context{ id: None, params: Vec::new(), seed: self.seed, root: self.seed, size: self.size, settings })?;
+
+		Ok(())
+	}
+
+	/// Fails if an item other than the one at `index` is stored under `key`.
+	fn check_key(&self, key: &str, index: Option<usize>) -> Result<(), StoreError> {
+		match self.items.iter().position(|(other, _)| other == key) {
+			Some(other) if Some(other) != index => Err(StoreError::DuplicateId(key.to_string())),
+			_ => Ok(()),
+		}
+	}
+
+	/// Replaces the item at `index`, which is then stored under its new `id` field, if it has one.
+	fn replace(&mut self, index: usize, item: serde_json::Value) -> Result<serde_json::Value, StoreError> {
+		let key = item_key(&item).unwrap_or_else(|| self.items[index].0.clone());
+		self.check_key(&key, Some(index))?;
+
+		self.items[index] = (key, item.clone());
+
+		Ok(item)
+	}
+
+	pub fn list(&self) -> Vec<serde_json::Value> {
+		self.items.iter().map(|(_, item)| item.clone()).collect()
+	}
+
+	pub fn get(&self, id: &str) -> Option<serde_json::Value> {
+		self.items.iter().find(|(key, _)| key == id).map(|(_, item)| item.clone())
+	}
+
+	/// Generates a new item and overwrites its fields with the ones in `body`.
+	pub fn create(&mut self, schemas: &HashMap<String, Schema>, settings: &Settings, body: serde_json::Value) -> Result<serde_json::Value, StoreError> {
+		let id = self.next.to_string();
+
+		let mut item = build_value(schemas, &self.item, &Context{ id: Some(id.clone()), params: Vec::new(), seed: self.seed, root: self.seed, size: self.size, settings });
+		merge(&mut item, body);
+
+		let key = item_key(&item).unwrap_or(id);
+		self.check_key(&key, None)?;
+
+		self.next += 1;
+		self.items.push((key, item.clone()));
+
+		Ok(item)
+	}
+
+	/// Replaces the item with `body`, keeping its `id` field.
+	pub fn update(&mut self, id: &str, body: serde_json::Value) -> Result<serde_json::Value, StoreError> {
+		let index = self.items.iter().position(|(key, _)| key == id).ok_or(StoreError::NotFound)?;
+
+		let mut item = body;
+
+		if let (Some(previous_id), serde_json::Value::Object(item)) = (self.items[index].1.get("id").cloned(), &mut item) {
+			item.insert("id".to_string(), previous_id);
+		}
+
+		self.replace(index, item)
+	}
+
+	/// Merges the fields in `body` into the item.
+	pub fn patch(&mut self, id: &str, body: serde_json::Value) -> Result<serde_json::Value, StoreError> {
+		let index = self.items.iter().position(|(key, _)| key == id).ok_or(StoreError::NotFound)?;
+
+		let mut item = self.items[index].1.clone();
+		merge(&mut item, body);
+
+		self.replace(index, item)
+	}
+
+	pub fn delete(&mut self, id: &str) -> Option<serde_json::Value> {
+		let index = self.items.iter().position(|(key, _)| key == id)?;
+
+		Some(self.items.remove(index).1)
+	}
+}
+
+fn merge(target: &mut serde_json::Value, source: serde_json::Value) {
+	match (target, source) {
+		(serde_json::Value::Object(target), serde_json::Value::Object(source)) => {
+			for (key, value) in source {
+				target.insert(key, value);
+			}
+		},
+		(target, source) => {
+			*target = source;
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{schemas::ingest_schemas, values::ObjectExpressions};
+
+	use super::*;
+
+	#[test]
+	fn test_store_crud() {
+		const STRING: &str = r#"
+		{
+			schemas: {
+				Person: {
+					fields: {
+						id: { template: "person-${this.id}" },
+						name: { template: "${FULL_NAME}" },
+						age: { range: { min: 18, max: 99 } },
+					},
+				},
+			},
+		}
+		"#;
+
+		let source: serde_json::Value = serde_json5::from_str(STRING).unwrap();
+		let schemas = ingest_schemas(&source);
+
+		let mut store = Store::seed(&schemas, &DataTypes::Object(ObjectExpressions::Schema("Person".to_string())), &Lengths::Scale, &Context{ id: None, params: vec![], seed: 0, root: 0, size: 16, settings: &Settings::default() }).unwrap();
+
+		assert_eq!(store.list().len(), 16);
+		assert_eq!(store.get("person-3").unwrap()["id"], "person-3");
+
+		let created = store.create(&schemas, &Settings::default(), serde_json::json!({ "name": "John Doe" })).unwrap();
+		assert_eq!(created["id"], "person-16");
+		assert_eq!(created["name"], "John Doe");
+		assert_eq!(store.get("person-16").unwrap(), created);
+
+		let patched = store.patch("person-16", serde_json::json!({ "age": 30 })).unwrap();
+		assert_eq!(patched["name"], "John Doe");
+		assert_eq!(patched["age"], 30);
+
+		let updated = store.update("person-16", serde_json::json!({ "name": "Jane Doe" })).unwrap();
+		assert_eq!(updated, serde_json::json!({ "id": "person-16", "name": "Jane Doe" }));
+
+		// Ids stay unique
+		assert_eq!(store.create(&schemas, &Settings::default(), serde_json::json!({ "id": "person-3" })), Err(StoreError::DuplicateId("person-3".to_string())));
+		assert_eq!(store.patch("person-16", serde_json::json!({ "id": "person-3" })), Err(StoreError::DuplicateId("person-3".to_string())));
+		assert_eq!(store.patch("person-404", serde_json::json!({})), Err(StoreError::NotFound));
+		assert_eq!(store.get("person-3").unwrap()["id"], "person-3");
+		assert_eq!(store.get("person-16").unwrap(), updated);
+
+		assert_eq!(store.delete("person-16").unwrap(), updated);
+		assert!(store.get("person-16").is_none());
+		assert!(store.delete("person-16").is_none());
+		assert_eq!(store.list().len(), 16);
+	}
+
+	#[test]
+	fn test_store_ids() {
+		const STRING: &str = r#"
+		{
+			schemas: {
+				Person: {
+					fields: {
+						id: { template: "${this.id::UUID}" },
+					},
+				},
+				Team: {
+					fields: {
+						id: "team",
+					},
+				},
+			},
+		}
+		"#;
+
+		let source: serde_json::Value = serde_json5::from_str(STRING).unwrap();
+		let schemas = ingest_schemas(&source);
+		let settings = Settings::default();
+		let ctx = Context{ id: None, params: vec![], seed: 0, root: 0, size: 16, settings: &settings };
+
+		let store = Store::seed(&schemas, &DataTypes::Object(ObjectExpressions::Schema("Person".to_string())), &Lengths::Count(256), &ctx).unwrap();
+		assert_eq!(store.list().len(), 256);
+
+		let store = Store::seed(&schemas, &DataTypes::Object(ObjectExpressions::Schema("Team".to_string())), &Lengths::Count(2), &ctx);
+		assert_eq!(store.unwrap_err(), StoreError::DuplicateId("team".to_string()));
+	}
+}
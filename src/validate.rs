@@ -0,0 +1,968 @@
+use std::{collections::{HashMap, HashSet}, fmt};
+
+use crate::{config::File, fake, routes::Methods, schemas::{parents, weighted_value, GENERATORS, MAX_DECIMALS, MAX_LENGTH}};
+
+/// An error in the configuration, with the JSON path of the offending value and, when it can be found, its position in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+	pub path: String,
+	pub message: String,
+	/// The file the error is in, set when the configuration is split across several files.
+	pub file: Option<String>,
+	/// One-based line and column.
+	pub location: Option<(usize, usize)>,
+}
+
+impl fmt::Display for ConfigError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let position = match (&self.file, self.location) {
+			(Some(file), Some((line, column))) => Some(format!("{}, line {}, column {}", file, line, column)),
+			(None, Some((line, column))) => Some(format!("line {}, column {}", line, column)),
+			(Some(file), None) => Some(file.clone()),
+			(None, None) => None,
+		};
+
+		match (self.path.is_empty(), position) {
+			(false, Some(position)) => write!(f, "{} ({}): ", self.path, position)?,
+			(false, None) => write!(f, "{}: ", self.path)?,
+			(true, Some(position)) => write!(f, "{}: ", position)?,
+			(true, None) => {},
+		}
+
+		write!(f, "{}", self.message)
+	}
+}
+
+/// Parses the configuration file, reporting syntax errors with their position.
+pub fn parse_source(text: &str) -> Result<serde_json::Value, ConfigError> {
+	serde_json5::from_str::<serde_json::Value>(text).map_err(|error| {
+		let serde_json5::Error::Message { msg, location } = error;
+
+		ConfigError { path: String::new(), message: msg, file: None, location: location.map(|location| (location.line, location.column)) }
+	})
+}
+
+/// Checks the whole configuration, merged from `files`, and returns every error found in it.
+pub fn validate(files: &[File], source: &serde_json::Value) -> Vec<ConfigError> {
+	let schemas = match source.get("schemas") {
+		Some(serde_json::Value::Object(schemas)) => schemas.clone(),
+		_ => serde_json::Map::new(),
+	};
+
+	let dictionaries = match source.get("dictionaries") {
+		Some(serde_json::Value::Object(dictionaries)) => dictionaries.clone(),
+		_ => serde_json::Map::new(),
+	};
+
+	let mut validator = Validator { files, schemas: &schemas, dictionaries: &dictionaries, errors: Vec::new() };
+
+	validator.validate_locale(source);
+	validator.validate_dictionaries(source);
+	validator.validate_schemas(source);
+	validator.validate_routes(&[], source);
+
+	let mut errors = validator.errors;
+	// Errors that can't be located go after the ones that can
+	errors.sort_by_key(|error| (error.file.as_ref().and_then(|file| files.iter().position(|f| f.path == *file)), error.location.is_none(), error.location));
+
+	errors
+}
+
+struct Validator<'a> {
+	files: &'a [File],
+	schemas: &'a serde_json::Map<String, serde_json::Value>,
+	dictionaries: &'a serde_json::Map<String, serde_json::Value>,
+	errors: Vec<ConfigError>,
+}
+
+impl<'a> Validator<'a> {
+	fn error(&mut self, path: &[&str], message: impl Into<String>) {
+		// The value is in the first file that has every key of the path
+		let found = self.files.iter().find_map(|file| locate(&file.text, path).map(|location| (file, location)));
+
+		self.errors.push(ConfigError {
+			path: format_path(path),
+			message: message.into(),
+			file: found.filter(|_| self.files.len() > 1).map(|(file, _)| file.path.clone()),
+			location: found.map(|(_, location)| location),
+		});
+	}
+
+	fn validate_locale(&mut self, source: &serde_json::Value) {
+		match source.get("locale") {
+			Some(serde_json::Value::String(locale)) if fake::find_locale(locale).is_none() => self.error(&["locale"], unknown_locale(locale)),
+			Some(serde_json::Value::String(_)) | None => {},
+			Some(_) => self.error(&["locale"], "`locale` must be a string"),
+		}
+	}
+
+	/// Checks the weighted values of an enum, like `{ value: "active", weight: 80 }`.
+	fn validate_enum(&mut self, path: &[&str], values: &[serde_json::Value]) {
+		let errors = self.errors.len();
+		let mut total = 0;
+
+		for (i, value) in values.iter().enumerate() {
+			let index = i.to_string();
+			let path = [path, &[index.as_str()]].concat();
+
+			// Objects with nothing but a `weight` are most likely weighted values missing their `value`,
+			// while objects with other keys are literals that happen to have a `weight` field
+			if let serde_json::Value::Object(value) = value {
+				if value.contains_key("weight") && value.keys().all(|key| key == "weight") {
+					self.error(&path, "weighted values must have a `value`");
+					continue;
+				}
+			}
+
+			match weighted_value(value).and_then(|(_, weight)| weight) {
+				Some(weight) => match weight.as_u64().filter(|weight| *weight <= u32::MAX as u64) {
+					Some(weight) => total += weight,
+					None => self.error(&[path.as_slice(), &["weight"]].concat(), "`weight` must be a non-negative integer"),
+				},
+				None => total += 1,
+			}
+		}
+
+		// Values with errors already explain why nothing can be picked
+		if total == 0 && self.errors.len() == errors {
+			self.error(path, "at least one value must have a weight greater than 0");
+		}
+	}
+
+	fn validate_dictionaries(&mut self, source: &serde_json::Value) {
+		match source.get("dictionaries") {
+			Some(serde_json::Value::Object(dictionaries)) => {
+				for (name, words) in dictionaries {
+					let path = ["dictionaries", name.as_str()];
+
+					match words {
+						serde_json::Value::Array(words) if words.is_empty() => self.error(&path, "a dictionary must have at least one word"),
+						serde_json::Value::Array(words) => {
+							for (i, word) in words.iter().enumerate() {
+								if !word.is_string() {
+									self.error(&[&path[..], &[i.to_string().as_str()]].concat(), "dictionary words must be strings");
+								}
+							}
+						},
+						_ => self.error(&path, "a dictionary must be an array of words or `{ file: \"words.txt\" }`"),
+					}
+				}
+			},
+			Some(_) => self.error(&["dictionaries"], "`dictionaries` must be an object"),
+			None => {},
+		}
+	}
+
+	fn validate_schemas(&mut self, source: &serde_json::Value) {
+		match source.get("schemas") {
+			Some(serde_json::Value::Object(schemas)) => {
+				for (name, schema) in schemas {
+					let path = ["schemas", name.as_str()];
+
+					match schema {
+						serde_json::Value::Object(schema) => {
+							self.validate_parents(&path, schema);
+							self.validate_schema(&path, schema);
+						},
+						_ => self.error(&path, "schema definitions must be objects"),
+					}
+				}
+
+				self.validate_cycles();
+			},
+			Some(_) => self.error(&["schemas"], "`schemas` must be an object"),
+			None => {},
+		}
+	}
+
+	fn validate_schema(&mut self, path: &[&str], schema: &serde_json::Map<String, serde_json::Value>) {
+		match schema.get("fields") {
+			Some(serde_json::Value::Object(fields)) => {
+				for (name, field) in fields {
+					if let serde_json::Value::Object(field) = field {
+						self.validate_field(&[path, &["fields", name.as_str()]].concat(), field);
+					}
+				}
+			},
+			Some(_) => self.error(&[path, &["fields"]].concat(), "`fields` must be an object"),
+			// Schemas can inherit all of their fields
+			None if schema.contains_key("extends") || schema.contains_key("allOf") => {},
+			None => self.error(path, "missing `fields`"),
+		}
+	}
+
+	/// Checks that `extends` and `allOf` name the schemas a schema inherits from.
+	fn validate_parents(&mut self, path: &[&str], schema: &serde_json::Map<String, serde_json::Value>) {
+		for key in ["extends", "allOf"] {
+			let path = [path, &[key]].concat();
+
+			match schema.get(key) {
+				Some(serde_json::Value::String(parent)) => self.validate_schema_name(&path, parent),
+				Some(serde_json::Value::Array(parents)) => {
+					for (i, parent) in parents.iter().enumerate() {
+						let index = i.to_string();
+						let path = [path.as_slice(), &[index.as_str()]].concat();
+
+						match parent {
+							serde_json::Value::String(parent) => self.validate_schema_name(&path, parent),
+							_ => self.error(&path, format!("`{}` must only have schema names", key)),
+						}
+					}
+				},
+				Some(_) => self.error(&path, format!("`{}` must be a schema name or an array of schema names", key)),
+				None => {},
+			}
+		}
+	}
+
+	/// The `fields` of a schema and of every schema it inherits from.
+	fn inherited_fields(&self, name: &str) -> Vec<&'a serde_json::Map<String, serde_json::Value>> {
+		let mut fields = Vec::new();
+		let mut visited = HashSet::new();
+		let mut stack = vec![name];
+
+		while let Some(name) = stack.pop() {
+			let Some((name, serde_json::Value::Object(schema))) = self.schemas.get_key_value(name) else {
+				continue;
+			};
+
+			if !visited.insert(name.as_str()) {
+				continue;
+			}
+
+			if let Some(serde_json::Value::Object(schema_fields)) = schema.get("fields") {
+				fields.push(schema_fields);
+			}
+
+			stack.extend(parents(schema));
+		}
+
+		fields
+	}
+
+	fn validate_field(&mut self, path: &[&str], field: &serde_json::Map<String, serde_json::Value>) {
+		let child = |key: &'a str| [path, &[key]].concat();
+
+		if !GENERATORS.iter().any(|key| field.contains_key(*key)) {
+			self.error(path, "missing a generator, like `template`, `range` or `enum`");
+		}
+
+		match field.get("template") {
+			Some(serde_json::Value::String(template)) => {
+				for message in check_template(template, self.dictionaries) {
+					self.error(&child("template"), message);
+				}
+			},
+			Some(_) => self.error(&child("template"), "`template` must be a string"),
+			None => {},
+		}
+
+		match field.get("range") {
+			Some(range @ (serde_json::Value::Object(_) | serde_json::Value::Array(_))) => {
+				let (min, max) = match range {
+					serde_json::Value::Array(bounds) if bounds.len() == 2 => (bounds.first(), bounds.get(1)),
+					serde_json::Value::Array(_) => (None, None),
+					_ => (range.get("min"), range.get("max")),
+				};
+
+				match (min, max) {
+					(Some(serde_json::Value::Number(min)), Some(serde_json::Value::Number(max))) if min.as_f64() > max.as_f64() => {
+						self.error(&child("range"), format!("minimum {} is greater than maximum {}", min, max));
+					},
+					(Some(serde_json::Value::Number(_)), Some(serde_json::Value::Number(_))) => {},
+					_ if range.is_array() => self.error(&child("range"), "`range` must be an array of two numbers, like `[18, 99]`"),
+					_ => self.error(&child("range"), "`range` must have numeric `min` and `max` values"),
+				}
+
+				if let Some(decimals) = range.get("decimals") {
+					if !decimals.as_u64().is_some_and(|decimals| decimals <= MAX_DECIMALS) {
+						self.error(&[path, &["range", "decimals"]].concat(), format!("`decimals` must be an integer from 0 to {}", MAX_DECIMALS));
+					}
+				}
+			},
+			Some(_) => self.error(&child("range"), "`range` must be an object with `min` and `max` values, or an array like `[18, 99]`"),
+			None => {},
+		}
+
+		for key in ["optional", "nullable"] {
+			if let Some(probability) = field.get(key) {
+				if !probability.as_f64().is_some_and(|probability| (0.0..=1.0).contains(&probability)) {
+					self.error(&child(key), format!("`{}` must be a probability from 0 to 1", key));
+				}
+			}
+		}
+
+		match field.get("bool") {
+			Some(serde_json::Value::Object(bool)) => {
+				if let Some(probability) = bool.get("probability") {
+					if !probability.as_f64().is_some_and(|probability| (0.0..=1.0).contains(&probability)) {
+						self.error(&[path, &["bool", "probability"]].concat(), "`probability` must be a number from 0 to 1");
+					}
+				}
+			},
+			Some(_) => self.error(&child("bool"), "`bool` must be an object, like `{ probability: 0.5 }`"),
+			None => {},
+		}
+
+		if let Some(date) = field.get("date") {
+			match date.get("frame") {
+				Some(serde_json::Value::String(frame)) if ["now", "future", "soon", "recent", "past"].contains(&frame.as_str()) => {},
+				Some(frame) => self.error(&[path, &["date", "frame"]].concat(), format!("unknown date frame {}, expected one of `now`, `future`, `soon`, `recent` or `past`", frame)),
+				None => {},
+			}
+		}
+
+		match field.get("schema") {
+			Some(serde_json::Value::String(schema)) => self.validate_schema_name(&child("schema"), schema),
+			Some(_) => self.error(&child("schema"), "`schema` must be the name of a schema"),
+			None => {},
+		}
+
+		match field.get("items") {
+			Some(serde_json::Value::Object(items)) => {
+				self.validate_field(&child("items"), items);
+				self.validate_lengths(path, field);
+			},
+			// Literal items are repeated as they are
+			Some(_) => self.validate_lengths(path, field),
+			None => {},
+		}
+
+		if field.contains_key("fields") {
+			self.validate_schema(path, field);
+		}
+
+		for key in ["enum", "values"] {
+			match field.get(key) {
+				Some(serde_json::Value::Array(values)) if values.is_empty() => self.error(&child(key), format!("`{}` must have at least one value", key)),
+				Some(serde_json::Value::Array(values)) => self.validate_enum(&child(key), values),
+				Some(_) => self.error(&child(key), format!("`{}` must be an array", key)),
+				None => {},
+			}
+		}
+
+		match field.get("ref") {
+			Some(serde_json::Value::String(reference)) => {
+				let (schema, referenced_field) = match reference.split_once('.') {
+					Some((schema, field)) => (schema, Some(field)),
+					None => (reference.as_str(), None),
+				};
+
+				if !self.schemas.contains_key(schema) {
+					self.error(&child("ref"), format!("unknown schema `{}`", schema));
+				} else if let Some(referenced_field) = referenced_field {
+					if self.inherited_fields(schema).iter().all(|fields| !fields.contains_key(referenced_field)) {
+						self.error(&child("ref"), format!("schema `{}` has no field `{}`", schema, referenced_field));
+					}
+				}
+			},
+			Some(_) => self.error(&child("ref"), "`ref` must be a string, like `Person` or `Person.id`"),
+			None => {},
+		}
+	}
+
+	fn validate_lengths(&mut self, path: &[&str], source: &serde_json::Map<String, serde_json::Value>) {
+		for key in ["count", "minItems", "maxItems"] {
+			if let Some(value) = source.get(key) {
+				if !value.as_u64().is_some_and(|length| length <= MAX_LENGTH) {
+					self.error(&[path, &[key]].concat(), format!("`{}` must be an integer from 0 to {}", key, MAX_LENGTH));
+				}
+			}
+		}
+
+		if let (Some(min), Some(max)) = (source.get("minItems").and_then(|v| v.as_u64()), source.get("maxItems").and_then(|v| v.as_u64())) {
+			if min > max {
+				self.error(&[path, &["minItems"]].concat(), format!("`minItems` {} is greater than `maxItems` {}", min, max));
+			}
+		}
+	}
+
+	fn validate_schema_name(&mut self, path: &[&str], schema: &str) {
+		if !self.schemas.contains_key(schema) {
+			self.error(path, format!("unknown schema `{}`", schema));
+		}
+	}
+
+	fn validate_routes(&mut self, path: &[&str], source: &serde_json::Value) {
+		let path = [path, &["routes"]].concat();
+
+		match source.get("routes") {
+			Some(serde_json::Value::Object(routes)) => {
+				for (name, route) in routes {
+					let path = [path.as_slice(), &[name.as_str()]].concat();
+
+					if !route.is_object() {
+						self.error(&path, "route definitions must be objects");
+						continue;
+					}
+
+					match route.get("method") {
+						Some(serde_json::Value::String(method)) if Methods::parse(method).is_some() => {},
+						Some(method) => self.error(&[path.as_slice(), &["method"]].concat(), format!("unknown method {}", method)),
+						None => {},
+					}
+
+					if let Some(response) = route.get("response") {
+						self.validate_response(&[path.as_slice(), &["response"]].concat(), response);
+					}
+
+					match route.get("methods") {
+						Some(serde_json::Value::Object(methods)) => {
+							for (method, definition) in methods {
+								let path = [path.as_slice(), &["methods", method.as_str()]].concat();
+
+								if Methods::parse(method).is_none() {
+									self.error(&path, format!("unknown method `{}`", method));
+								}
+
+								if let Some(response) = definition.get("response") {
+									self.validate_response(&[path.as_slice(), &["response"]].concat(), response);
+								}
+							}
+						},
+						Some(_) => self.error(&[path.as_slice(), &["methods"]].concat(), "`methods` must be an object"),
+						None => {},
+					}
+
+					if route.get("store") == Some(&serde_json::Value::Bool(true)) && !get_response(route).is_some_and(is_array_response) {
+						self.error(&[path.as_slice(), &["store"]].concat(), "`store` needs a GET response with an array of objects, like `\"Person[]\"`");
+					}
+
+					self.validate_routes(&path, route);
+				}
+			},
+			Some(_) => self.error(&path, "`routes` must be an object"),
+			None => {},
+		}
+	}
+
+	fn validate_response(&mut self, path: &[&str], response: &serde_json::Value) {
+		let child = |key: &'a str| [path, &[key]].concat();
+
+		let response = match response {
+			serde_json::Value::String(schema) => {
+				self.validate_schema_name(path, &schema.replace("[]", ""));
+				return;
+			},
+			serde_json::Value::Object(response) => response,
+			_ => {
+				self.error(path, "`response` must be a schema name or an object");
+				return;
+			},
+		};
+
+		match response.get("schema") {
+			Some(serde_json::Value::String(schema)) => self.validate_schema_name(&child("schema"), &schema.replace("[]", "")),
+			Some(serde_json::Value::Object(schema)) => {
+				match schema.get("items") {
+					Some(serde_json::Value::Object(items)) => match items.get("schema") {
+						Some(serde_json::Value::Object(item)) => self.validate_schema(&[path, &["schema", "items", "schema"]].concat(), item),
+						_ => self.error(&[path, &["schema", "items"]].concat(), "`items` must have an inline `schema`"),
+					},
+					Some(_) => self.error(&[path, &["schema", "items"]].concat(), "`items` must be an object"),
+					None => self.validate_schema(&child("schema"), schema),
+				}
+			},
+			Some(_) => self.error(&child("schema"), "`schema` must be a schema name or an inline schema"),
+			None => {},
+		}
+
+		if let Some(status) = response.get("status") {
+			if !status.as_u64().is_some_and(|status| (100..=599).contains(&status)) {
+				self.error(&child("status"), format!("{} is not a valid status code", status));
+			}
+		}
+
+		match response.get("headers") {
+			Some(serde_json::Value::Object(headers)) => {
+				for (name, value) in headers {
+					if let serde_json::Value::String(template) = value {
+						for message in check_template(template, self.dictionaries) {
+							self.error(&[path, &["headers", name.as_str()]].concat(), message);
+						}
+					}
+				}
+			},
+			Some(_) => self.error(&child("headers"), "`headers` must be an object"),
+			None => {},
+		}
+
+		self.validate_lengths(path, response);
+	}
+
+	/// Reports schemas that inherit from or embed themselves, which would never resolve or generate endlessly.
+	fn validate_cycles(&mut self) {
+		fn embedded<'b>(field: &'b serde_json::Value, embeds: &mut Vec<&'b str>) {
+			if let Some(serde_json::Value::String(schema)) = field.get("schema") {
+				embeds.push(schema);
+			}
+
+			if let Some(items) = field.get("items") {
+				embedded(items, embeds);
+			}
+
+			// Dotted refs, like `Person.id`, only generate the field they point to
+			if let Some(serde_json::Value::String(reference)) = field.get("ref") {
+				embeds.push(reference);
+			}
+
+			if let Some(serde_json::Value::Object(fields)) = field.get("fields") {
+				fields.values().for_each(|field| embedded(field, embeds));
+			}
+		}
+
+		let mut inheritance = HashMap::new();
+		let mut embeds = HashMap::new();
+
+		for (name, schema) in self.schemas {
+			if let serde_json::Value::Object(schema) = schema {
+				inheritance.insert(name.as_str(), parents(schema));
+			}
+
+			// Inherited fields embed schemas as well
+			let mut schema_embeds = Vec::new();
+
+			for fields in self.inherited_fields(name) {
+				fields.values().for_each(|field| embedded(field, &mut schema_embeds));
+			}
+
+			embeds.insert(name.as_str(), schema_embeds);
+		}
+
+		// Fields pointed to by dotted refs are nodes of their own, embedding what the field embeds
+		let mut pending = embeds.values().flatten().copied().filter(|name| name.contains('.')).collect::<Vec<_>>();
+
+		while let Some(reference) = pending.pop() {
+			if embeds.contains_key(reference) {
+				continue;
+			}
+
+			let (schema, field) = reference.split_once('.').unwrap_or((reference, ""));
+			let mut field_embeds = Vec::new();
+
+			if let Some(field) = self.inherited_fields(schema).into_iter().find_map(|fields| fields.get(field)) {
+				embedded(field, &mut field_embeds);
+			}
+
+			pending.extend(field_embeds.iter().copied().filter(|name| name.contains('.')));
+			embeds.insert(reference, field_embeds);
+		}
+
+		self.report_cycles(&inheritance, "inherits from");
+		self.report_cycles(&embeds, "embeds");
+	}
+
+	/// Reports each cycle of `graph` once, on the first of its schemas, or `Schema.field` fields, by name.
+	fn report_cycles(&mut self, graph: &HashMap<&str, Vec<&str>>, relation: &str) {
+		let mut names = graph.keys().copied().collect::<Vec<_>>();
+		names.sort();
+
+		let mut reported = HashSet::new();
+
+		for name in names {
+			let mut stack = vec![(name, vec![name])];
+
+			while let Some((current, trail)) = stack.pop() {
+				for &next in graph.get(current).into_iter().flatten() {
+					if next == name {
+						if trail.iter().all(|schema| !reported.contains(schema)) {
+							reported.insert(name);
+
+							match name.split_once('.') {
+								Some((schema, field)) => self.error(&["schemas", schema, "fields", field], format!("field {} itself through {} -> {}", relation, trail.join(" -> "), name)),
+								None => self.error(&["schemas", name], format!("schema {} itself through {} -> {}", relation, trail.join(" -> "), name)),
+							}
+						}
+					} else if !trail.contains(&next) {
+						stack.push((next, [trail.as_slice(), &[next]].concat()));
+					}
+				}
+			}
+		}
+	}
+}
+
+fn unknown_locale(locale: &str) -> String {
+	format!("unknown locale `{}`, expected one of {}", locale, fake::LOCALES.iter().map(|locale| locale.name).collect::<Vec<_>>().join(", "))
+}
+
+/// Returns a message for every malformed expression in a template, and for every dictionary it uses that isn't in `dictionaries`.
+/// The response a route serves for GET, the one under `methods` taking precedence over the route level `response`.
+fn get_response(route: &serde_json::Value) -> Option<&serde_json::Value> {
+	if let Some(serde_json::Value::Object(methods)) = route.get("methods") {
+		if let Some((_, definition)) = methods.iter().find(|(method, _)| Methods::parse(method) == Some(Methods::Get)) {
+			return definition.get("response");
+		}
+	}
+
+	// The route level `response` is served under `method`, or GET if none was given
+	let method = route.get("method").and_then(|method| method.as_str()).and_then(Methods::parse).unwrap_or(Methods::Get);
+
+	route.get("response").filter(|_| method == Methods::Get)
+}
+
+/// Whether a response generates an array of objects, the only kind of response a store can hold.
+fn is_array_response(response: &serde_json::Value) -> bool {
+	match response {
+		serde_json::Value::String(schema) => schema.contains("[]"),
+		serde_json::Value::Object(response) => match response.get("schema") {
+			Some(serde_json::Value::String(schema)) => schema.contains("[]"),
+			Some(serde_json::Value::Object(schema)) => schema.get("items").and_then(|items| items.get("schema")).is_some_and(|schema| schema.is_object()),
+			_ => false,
+		},
+		_ => false,
+	}
+}
+
+pub fn check_template(template: &str, dictionaries: &serde_json::Map<String, serde_json::Value>) -> Vec<String> {
+	let mut messages = Vec::new();
+	let mut rest = template;
+
+	while let Some(start) = rest.find("${") {
+		let Some(end) = rest[start..].find('}') else {
+			messages.push(format!("unterminated expression `{}`", &rest[start..]));
+			break;
+		};
+
+		let expression = &rest[start + 2..start + end];
+
+		if expression.is_empty() {
+			messages.push("empty expression `${}`".to_string());
+		} else if let Some((min, max)) = expression.split_once("..") {
+			match (min.trim().parse::<i64>(), max.trim().parse::<i64>()) {
+				(Ok(min), Ok(max)) if min > max => messages.push(format!("range `${{{}}}` has its minimum greater than its maximum", expression)),
+				(Ok(_), Ok(_)) => {},
+				_ => messages.push(format!("range `${{{}}}` must have integer bounds", expression)),
+			}
+		} else if let Some(name) = expression.strip_prefix("dict.") {
+			if !dictionaries.contains_key(name) {
+				messages.push(format!("unknown dictionary `{}`", name));
+			}
+		} else if !expression.starts_with("params.") && !["this.id", "this.id::UUID", "this.id::UUIDv4"].contains(&expression) {
+			let (name, locale) = match expression.split_once(':') {
+				Some((name, locale)) => (name, Some(locale)),
+				None => (expression, None),
+			};
+
+			if !fake::is_variable(name) {
+				messages.push(format!("unknown variable `{}`", name));
+			} else if let Some(locale) = locale.filter(|locale| fake::find_locale(locale).is_none()) {
+				messages.push(unknown_locale(locale));
+			}
+		}
+
+		rest = &rest[start + end + 1..];
+	}
+
+	messages
+}
+
+pub fn format_path(path: &[&str]) -> String {
+	path.iter().map(|segment| {
+		if !segment.is_empty() && segment.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$') {
+			segment.to_string()
+		} else {
+			format!("{:?}", segment)
+		}
+	}).collect::<Vec<_>>().join(".")
+}
+
+/// Finds the position of the value at `path` by looking for each of its keys, or array indices, in turn.
+/// Since the parsed value doesn't keep positions this is a best effort, and may miss keys written in unusual ways.
+pub fn locate(text: &str, path: &[&str]) -> Option<(usize, usize)> {
+	let mut offset = 0;
+	// Whether `offset` is at a key rather than at the value itself, like an array element
+	let mut at_key = false;
+
+	for segment in path {
+		let value = match at_key {
+			true => offset + text[offset..].find(':')? + 1,
+			false => offset,
+		};
+
+		match segment.parse::<usize>().ok().and_then(|index| find_element(text, value, index)) {
+			Some(element) => {
+				offset = element;
+				at_key = false;
+			},
+			None => {
+				offset = find_key(text, offset, segment)?;
+				at_key = true;
+			},
+		}
+	}
+
+	let before = &text[..offset];
+	let line = before.matches('\n').count() + 1;
+	let column = before.rsplit('\n').next().map_or(0, |line| line.chars().count()) + 1;
+
+	Some((line, column))
+}
+
+/// Finds the position of the `index`th element of the array that starts at `from`, possibly after some whitespace.
+fn find_element(text: &str, from: usize, index: usize) -> Option<usize> {
+	let bytes = text.as_bytes();
+	let start = from + text[from..].find(|c: char| !c.is_whitespace())?;
+
+	if bytes[start] != b'[' {
+		return None;
+	}
+
+	let mut depth = 0;
+	let mut element = 0;
+	// Whether the next value starts an element, right after the opening bracket or a comma
+	let mut expecting = true;
+	let mut i = start;
+
+	while i < bytes.len() {
+		let byte = bytes[i];
+
+		// Compares bytes rather than slicing the text, since `i` can be in the middle of a multibyte character
+		if bytes[i..].starts_with(b"//") {
+			i += bytes[i..].iter().position(|byte| *byte == b'\n').unwrap_or(bytes.len() - i);
+			continue;
+		}
+
+		if bytes[i..].starts_with(b"/*") {
+			i += bytes[i..].windows(2).position(|end| end == b"*/").map_or(bytes.len() - i, |end| end + 2);
+			continue;
+		}
+
+		if byte.is_ascii_whitespace() {
+			i += 1;
+			continue;
+		}
+
+		if depth == 1 && expecting && byte != b']' && byte != b',' {
+			if element == index {
+				return Some(i);
+			}
+
+			element += 1;
+			expecting = false;
+		}
+
+		match byte {
+			b'[' | b'{' => depth += 1,
+			b']' | b'}' => {
+				depth -= 1;
+
+				if depth == 0 {
+					return None;
+				}
+			},
+			b',' if depth == 1 => expecting = true,
+			b'"' | b'\'' => {
+				// Skips the string, along with any escaped quotes in it
+				i += 1;
+
+				while i < bytes.len() && bytes[i] != byte {
+					i += if bytes[i] == b'\\' { 2 } else { 1 };
+				}
+			},
+			_ => {},
+		}
+
+		i += 1;
+	}
+
+	None
+}
+
+fn find_key(text: &str, from: usize, key: &str) -> Option<usize> {
+	let is_identifier = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+
+	let candidates = [format!("\"{}\"", key), format!("'{}'", key), key.to_string()];
+
+	candidates.iter().enumerate().filter_map(|(i, candidate)| {
+		let mut start = from;
+
+		while let Some(found) = text[start..].find(candidate.as_str()) {
+			let position = start + found;
+			let end = position + candidate.len();
+			start = position + candidate.len().max(1);
+
+			// Bare keys must not be part of a longer identifier
+			if i == 2 && (text[..position].chars().next_back().is_some_and(is_identifier) || text[end..].chars().next().is_some_and(is_identifier)) {
+				continue;
+			}
+
+			if text[end..].trim_start().starts_with(':') {
+				return Some(position);
+			}
+		}
+
+		None
+	}).min()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_check_template() {
+		let dictionaries = serde_json::json!({ "branches": ["Palermo"] }).as_object().unwrap().clone();
+
+		assert!(check_template("My name is ${FULL_NAME} and I am ${18..99}", &dictionaries).is_empty());
+		assert_eq!(check_template("${FULL_NAME", &dictionaries), vec!["unterminated expression `${FULL_NAME`".to_string()]);
+		assert_eq!(check_template("${99..18}", &dictionaries), vec!["range `${99..18}` has its minimum greater than its maximum".to_string()]);
+		assert_eq!(check_template("${a..b} ${}", &dictionaries), vec!["range `${a..b}` must have integer bounds".to_string(), "empty expression `${}`".to_string()]);
+		assert!(check_template("${FULL_NAME:es_AR} ${this.id::UUID}", &dictionaries).is_empty());
+		assert_eq!(check_template("${CITY:fr_FR}", &dictionaries), vec!["unknown locale `fr_FR`, expected one of en_US, es_AR, de_DE".to_string()]);
+		assert!(check_template("${dict.branches}", &dictionaries).is_empty());
+		assert_eq!(check_template("${dict.skus}", &dictionaries), vec!["unknown dictionary `skus`".to_string()]);
+		assert!(check_template("${params.orgId} ${this.id} ${FIELD.value} ${1 .. 5}", &dictionaries).is_empty());
+		assert_eq!(check_template("${FULL_NAM} ${FULL_NAM:es_AR}", &dictionaries), vec!["unknown variable `FULL_NAM`".to_string(), "unknown variable `FULL_NAM`".to_string()]);
+	}
+
+	#[test]
+	fn test_locate() {
+		let text = "{\n\tvalues: [\"a, ]\", /* [ */ { weight: 1 }, [1, [2]], 'b' ],\n}";
+
+		assert_eq!(locate(text, &["values"]), Some((2, 2)));
+		assert_eq!(locate(text, &["values", "0"]), Some((2, 11)));
+		assert_eq!(locate(text, &["values", "1", "weight"]), Some((2, 29)));
+		assert_eq!(locate(text, &["values", "2", "1", "0"]), Some((2, 47)));
+		assert_eq!(locate(text, &["values", "3"]), Some((2, 52)));
+		assert_eq!(locate(text, &["values", "4"]), None);
+
+		let text = "[{ año: 1, weight: -1 }, // ñ\n 'é' ]";
+		assert_eq!(locate(text, &["0", "weight"]), Some((1, 12)));
+		assert_eq!(locate(text, &["1"]), Some((2, 2)));
+	}
+
+	#[test]
+	fn test_store_response() {
+		let route = |json: &str| serde_json5::from_str::<serde_json::Value>(json).unwrap();
+		let stored = |json: &str| get_response(&route(json)).is_some_and(is_array_response);
+
+		assert!(stored(r#"{ response: "Person[]" }"#));
+		assert!(stored(r#"{ response: { schema: { items: { schema: { fields: {} } } } } }"#));
+		assert!(stored(r#"{ response: "Person", methods: { GET: { response: { schema: "Person[]" } } } }"#));
+		assert!(!stored(r#"{ response: "Person[]", methods: { get: {} } }"#));
+		assert!(!stored(r#"{ response: "Person[]", method: "post" }"#));
+		assert!(!stored(r#"{ methods: { post: { response: "Person[]" } } }"#));
+	}
+
+	#[test]
+	fn test_parse_source() {
+		let error = parse_source("{\n\tschemas: {\n}").unwrap_err();
+		assert!(error.location.is_some());
+		assert!(error.path.is_empty());
+	}
+
+	#[test]
+	fn test_validate() {
+		const STRING: &str = r#"{
+	locale: "pt_BR",
+	dictionaries: {
+		branches: [],
+		skus: "skus.txt",
+	},
+	schemas: {
+		Person: {
+			fields: {
+				name: { template: "${FULL_NAME" },
+				age: { range: { min: 99, max: 18 } },
+				score: { range: { min: 0.5, max: 9.5, decimals: 12 } },
+				active: { bool: { probability: 2 }, optional: true },
+				friends: { items: { schema: "Friend" }, minItems: 5, maxItems: 1 },
+				tags: { items: { enum: [] }, count: 2000000 },
+				level: { range: [1, 2, 3] },
+				employer: { ref: "Company.name" },
+				branch: { template: "${dict.regions}" },
+				role: { values: [{ value: "admin", weight: -1 }, { weight: 2 }] },
+				status: { enum: [{ value: "banned", weight: 0 }] },
+			},
+		},
+		Company: {
+			id: { template: "${this.id}" },
+		},
+		Node: {
+			fields: {
+				children: { items: { schema: "Node" } },
+			},
+		},
+		Entity: {
+			extends: "Timestamped",
+			allOf: ["Audited", 7],
+		},
+		Timestamped: {
+			extends: "Entity",
+			fields: {
+				createdAt: { date: { frame: "past" } },
+			},
+		},
+		Employee: {
+			extends: "Person",
+			fields: {
+				manager: { ref: "Employee.employer" },
+			},
+		},
+		Member: {
+			fields: {
+				id: { template: "${this.id}" },
+				managerId: { ref: "Member.id" },
+				mentorId: { ref: "Member.buddyId" },
+				buddyId: { ref: "Member.mentorId" },
+				size: { enum: [{ name: "Small", weight: 2 }, { name: "Large", weight: -5 }] },
+				nickname: { template: "${FULL_NAM}" },
+				title: { tempalte: "${JOB}" },
+			},
+		},
+	},
+	routes: {
+		"/people": {
+			response: { schema: "People[]", status: 1000 },
+			routes: {
+				"/:id": {
+					methods: { fetch: { response: "Person" } },
+				},
+			},
+		},
+		"/teams": {
+			response: "Company",
+			store: true,
+		},
+	},
+}"#;
+
+		let source = parse_source(STRING).unwrap();
+
+		let errors = validate(&[File { path: "mocks.json5".to_string(), text: STRING.to_string() }], &source).into_iter().map(|error| error.to_string()).collect::<Vec<_>>();
+
+		assert_eq!(errors, vec![
+			"locale (line 2, column 2): unknown locale `pt_BR`, expected one of en_US, es_AR, de_DE",
+			"dictionaries.branches (line 4, column 3): a dictionary must have at least one word",
+			"dictionaries.skus (line 5, column 3): a dictionary must be an array of words or `{ file: \"words.txt\" }`",
+			"schemas.Person.fields.name.template (line 10, column 13): unterminated expression `${FULL_NAME`",
+			"schemas.Person.fields.age.range (line 11, column 12): minimum 99 is greater than maximum 18",
+			"schemas.Person.fields.score.range.decimals (line 12, column 43): `decimals` must be an integer from 0 to 9",
+			"schemas.Person.fields.active.bool.probability (line 13, column 23): `probability` must be a number from 0 to 1",
+			"schemas.Person.fields.active.optional (line 13, column 41): `optional` must be a probability from 0 to 1",
+			"schemas.Person.fields.friends.items.schema (line 14, column 25): unknown schema `Friend`",
+			"schemas.Person.fields.friends.minItems (line 14, column 45): `minItems` 5 is greater than `maxItems` 1",
+			"schemas.Person.fields.tags.items.enum (line 15, column 22): `enum` must have at least one value",
+			"schemas.Person.fields.tags.count (line 15, column 34): `count` must be an integer from 0 to 1000000",
+			"schemas.Person.fields.level.range (line 16, column 14): `range` must be an array of two numbers, like `[18, 99]`",
+			"schemas.Person.fields.employer.ref (line 17, column 17): schema `Company` has no field `name`",
+			"schemas.Person.fields.branch.template (line 18, column 15): unknown dictionary `regions`",
+			"schemas.Person.fields.role.values.0.weight (line 19, column 40): `weight` must be a non-negative integer",
+			"schemas.Person.fields.role.values.1 (line 19, column 54): weighted values must have a `value`",
+			"schemas.Person.fields.status.enum (line 20, column 15): at least one value must have a weight greater than 0",
+			"schemas.Company (line 23, column 3): missing `fields`",
+			"schemas.Node (line 26, column 3): schema embeds itself through Node -> Node",
+			"schemas.Entity (line 31, column 3): schema inherits from itself through Entity -> Timestamped -> Entity",
+			"schemas.Entity.allOf.0 (line 33, column 12): unknown schema `Audited`",
+			"schemas.Entity.allOf.1 (line 33, column 23): `allOf` must only have schema names",
+			"schemas.Member.fields.buddyId (line 52, column 5): field embeds itself through Member.buddyId -> Member.mentorId -> Member.buddyId",
+			"schemas.Member.fields.nickname.template (line 54, column 17): unknown variable `FULL_NAM`",
+			"schemas.Member.fields.title (line 55, column 5): missing a generator, like `template`, `range` or `enum`",
+			"routes.\"/people\".response.schema (line 61, column 16): unknown schema `People`",
+			"routes.\"/people\".response.status (line 61, column 36): 1000 is not a valid status code",
+			"routes.\"/people\".routes.\"/:id\".methods.fetch (line 64, column 17): unknown method `fetch`",
+			"routes.\"/teams\".store (line 70, column 4): `store` needs a GET response with an array of objects, like `\"Person[]\"`",
+		]);
+	}
+}
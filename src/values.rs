@@ -1,21 +1,54 @@
-use std::{collections::HashMap, hash::{Hash, Hasher}};
+use std::collections::HashMap;
 
-use crate::{fake, schemas::{Field, Schema,}};
+use crate::{fake, random, schemas::{Field, Schema,}, settings::Settings};
 
-pub struct Context {
+pub struct Context<'a> {
 	pub id: Option<String>,
+	/// The route parameters of the request, in the order they appear in the route.
+	pub params: Vec<(String, String)>,
 	pub seed: usize,
+	/// The seed the request started from, before any id was mixed in.
+	/// References generate the objects they point to from it, like their collection routes do.
+	pub root: usize,
 	pub size: usize,
+	pub settings: &'a Settings,
+}
+
+impl<'a> Context<'a> {
+	/// Builds the context of a request from its route parameters.
+	/// The last parameter is the id, and the seed is the key of the collection it's an item of, the route without that parameter.
+	/// That way `/people/:id` generates the same person as the matching item of `/people`, and so do nested routes like `/orgs/:orgId/people/:personId`.
+	pub fn from_params(params: Vec<(String, String)>, seed: usize, size: usize, settings: &'a Settings) -> Context<'a> {
+		let id = params.last().map(|(_, value)| value.clone());
+
+		let root = seed;
+		let seed = match params.split_last() {
+			Some((_, collection)) => Context::from_params(collection.to_vec(), root, size, settings).key(),
+			None => root,
+		};
+
+		Context { id, params, seed, root, size, settings }
+	}
+
+	/// The key every value of this context is generated from, derived from the seed and the id.
+	fn key(&self) -> usize {
+		match self.id.as_ref() {
+			Some(id) => random::derive(self.seed, id),
+			None => random::scramble(self.seed),
+		}
+	}
+}
+
+/// Picks a number between `min` and `max`, both inclusive, from the key.
+/// A maximum lower than the minimum always picks the minimum.
+fn pick(min: i64, max: i64, key: usize) -> i64 {
+	let span = (max as i128 - min as i128).max(0) + 1;
+
+	(min as i128 + (key as i128).rem_euclid(span)) as i64
 }
 
 pub fn build_value(schemas: &HashMap<String, Schema>, datatype: &DataTypes, ctx: &Context) -> serde_json::Value {
-	let hashed_key = if let Some(id) = ctx.id.as_ref() {
-		let mut hasher = std::collections::hash_map::DefaultHasher::new();
-		id.hash(&mut hasher);
-		(hasher.finish() as usize).wrapping_add(ctx.seed)
-	} else {
-		ctx.seed
-	};
+	let hashed_key = ctx.key();
 
 	let val = match datatype {
 		DataTypes::String(expressions) => {
@@ -27,36 +60,29 @@ pub fn build_value(schemas: &HashMap<String, Schema>, datatype: &DataTypes, ctx:
 						res_string.push_str(&s);
 					},
 					StringExpressions::Range(min, max) => {
-						let val = min + (hashed_key as i64).rem_euclid(max - min);
+						let val = pick(*min, *max, hashed_key);
 						res_string.push_str(&val.to_string());
 					},
 					StringExpressions::Variable(s) => {
 						match s.as_str() {
-							"FULL_NAME" => {
-								res_string.push_str(fake::get_fake_full_name(hashed_key).as_str());
-							},
-							"FIELD.name" => {
-								let name = fake::get_fake_field_name(hashed_key);
-								res_string.push_str(name);
-							},
-							"FIELD.value" => {
-								let value = fake::get_fake_field_value(hashed_key);
-								res_string.push_str(value);
-							},
 							"this.id" => {
 								res_string.push_str(ctx.id.as_ref().map_or("", |f| f.as_str()));
 							},
 							"this.id::UUID" | "this.id::UUIDv4" => {
 								res_string.push_str(&fake::get_fake_uuidv4(hashed_key));
 							},
-							"ADJECTIVE" => {
-								res_string.push_str(fake::get_fake_adjective(hashed_key));
+							s if s.starts_with("params.") => {
+								let name = &s["params.".len()..];
+								res_string.push_str(ctx.params.iter().find(|(param, _)| param == name).map_or("", |(_, value)| value.as_str()));
 							},
-							"ROLE" => {
-								res_string.push_str(fake::get_fake_role_name(hashed_key));
+							s if s.starts_with("dict.") => {
+								res_string.push_str(ctx.settings.dictionary_word(&s["dict.".len()..], hashed_key).unwrap_or(""));
 							},
 							_ => {
-								res_string.push_str(s.as_str());
+								match fake::get_fake_variable(s, hashed_key, ctx.settings.locale) {
+									Some(value) => res_string.push_str(&value),
+									None => res_string.push_str(s.as_str()),
+								}
 							}
 						}
 					},
@@ -89,49 +115,43 @@ pub fn build_value(schemas: &HashMap<String, Schema>, datatype: &DataTypes, ctx:
 			serde_json::Value::String(res_string)
 		},
 		DataTypes::Enum(values) => {
-			let val = hashed_key % values.len();
-			serde_json::Value::String(values[val].clone())
+			pick_weighted(values, hashed_key).cloned().unwrap_or(serde_json::Value::Null)
 		},
-		DataTypes::Array(expression) => {
+		DataTypes::Array(item, length) => {
 			let mut arr = Vec::new();
 
-			for i in 0..16 {
+			for i in 0..length.resolve(ctx.size, hashed_key) {
 				let id = format!("{}", i);
 
-				match expression {
-					ObjectExpressions::Schema(schema_name) => {
-						let schema = schemas.get(schema_name).expect("Schema not found");
-						arr.push(serde_json::Value::Object(build_object(schemas, (&schema.fields), &Context{ id: Some(id), seed: hashed_key, size: ctx.size })));
-					},
-					ObjectExpressions::Object(fields) => {
-						arr.push(serde_json::Value::Object(build_object(schemas, &fields, &Context{ id: Some(id), seed: hashed_key, size: ctx.size })));
-					},
-				}
+				arr.push(build_value(schemas, item, &Context{ id: Some(id), params: ctx.params.clone(), seed: hashed_key, root: ctx.root, size: ctx.size, settings: ctx.settings }));
 			}
 
 			serde_json::Value::Array(arr)
 		},
 		DataTypes::Number(expression) => {
-			let mut val = 0;
-
-			match expression {
-				NumberExpressions::Literal(v) => {
-					val = *v;
+			let val = match expression {
+				NumberExpressions::Literal(number) => {
+					return serde_json::Value::Number(number.clone());
 				},
 				NumberExpressions::Range(min, max) => {
-					val = min + (hashed_key as i64).rem_euclid(max - min);
+					pick(*min, *max, hashed_key)
+				},
+				NumberExpressions::Decimal(min, max, decimals) => {
+					let units = pick(*min, *max, hashed_key);
+
+					if *decimals == 0 {
+						return serde_json::Value::from(units);
+					}
+
+					return serde_json::Number::from_f64(units as f64 / 10f64.powi(*decimals as i32)).map_or(serde_json::Value::Null, serde_json::Value::Number);
 				},
 				NumberExpressions::Variable(s) => {
 					match s.as_str() {
-						"this.id" => {
-							val = hashed_key as i64;
-						},
-						_ => {
-							val = s.parse::<i64>().unwrap();
-						}
+						"this.id" => hashed_key as i64,
+						_ => s.parse::<i64>().unwrap(),
 					}
 				}
-			}
+			};
 
 			serde_json::Value::Number(serde_json::Number::from(val))
 		}
@@ -148,6 +168,49 @@ pub fn build_value(schemas: &HashMap<String, Schema>, datatype: &DataTypes, ctx:
 
 			serde_json::Value::Object(obj)
 		},
+		DataTypes::Reference(schema_name, field) => {
+			let schema = schemas.get(schema_name).expect("Schema not found");
+
+			// Keyed like the items of the schema's collection route, which has no id
+			let root_key = random::scramble(ctx.root);
+			let length = schema.collection.as_ref().unwrap_or(&Lengths::Scale).resolve(ctx.size, root_key);
+
+			if length == 0 {
+				serde_json::Value::Null
+			} else {
+				let id = (hashed_key % length).to_string();
+				let item = Context{ id: Some(id), params: Vec::new(), seed: root_key, root: ctx.root, size: ctx.size, settings: ctx.settings };
+
+				match field {
+					// Only the referenced field is generated, so references to fields of the same schema don't recurse
+					Some(field) => match schema.fields.iter().find(|schema_field| schema_field.name == *field) {
+						Some(field) => build_field(schemas, field, &item, item.key()).unwrap_or(serde_json::Value::Null),
+						None => serde_json::Value::Null,
+					},
+					None => serde_json::Value::Object(build_object(schemas, &schema.fields, &item)),
+				}
+			}
+		},
+		DataTypes::List(values) => {
+			serde_json::Value::Array(values.iter().map(|value| build_value(schemas, value, ctx)).collect())
+		},
+		DataTypes::Boolean(expression) => {
+			let val = match expression {
+				BooleanExpressions::Literal(value) => *value,
+				BooleanExpressions::Chance(chance) => happens(hashed_key, *chance),
+			};
+
+			serde_json::Value::Bool(val)
+		},
+		DataTypes::Optional(_, datatype) => build_value(schemas, datatype, ctx),
+		DataTypes::Nullable(chance, datatype) => {
+			// A different part of the key than `Optional`'s, so both can happen independently
+			if happens(hashed_key / CHANCE_SCALE, *chance) {
+				serde_json::Value::Null
+			} else {
+				build_value(schemas, datatype, ctx)
+			}
+		},
 		DataTypes::Null => serde_json::Value::Null,
 	};
 
@@ -155,23 +218,47 @@ pub fn build_value(schemas: &HashMap<String, Schema>, datatype: &DataTypes, ctx:
 }
 
 pub fn build_object(schemas: &HashMap<String, Schema>, (fields): &(Vec<Field>), ctx: &Context) -> serde_json::value::Map<String, serde_json::Value> {
-	let hashed_key = if let Some(id) = ctx.id.as_ref() {
-		let mut hasher = std::collections::hash_map::DefaultHasher::new();
-		id.hash(&mut hasher);
-		(hasher.finish() as usize).wrapping_add(ctx.seed)
-	} else {
-		ctx.seed
-	};
+	let hashed_key = ctx.key();
 
 	let mut obj = serde_json::value::Map::new();
 
 	for field in fields {
-		obj.insert(field.name.clone(), build_value(schemas, &field.datatype, &Context{ id: ctx.id.to_owned(), seed: hashed_key, size: ctx.size }));
+		if let Some(value) = build_field(schemas, field, ctx, hashed_key) {
+			obj.insert(field.name.clone(), value);
+		}
 	}
 
 	obj
 }
 
+/// Generates a field of the object keyed by `object_key`, or returns `None` if the field is left out.
+fn build_field(schemas: &HashMap<String, Schema>, field: &Field, ctx: &Context, object_key: usize) -> Option<serde_json::Value> {
+	// Each field gets its own seed so fields of the same object don't correlate. Fields of nested objects
+	// are derived from their parent's, so the whole path to a field is mixed into it.
+	// Fields using `FIELD.name` or `FIELD.value` share one instead, so the value matches the name.
+	let field_seed = if uses_field_variable(&field.datatype) {
+		random::derive(object_key, "FIELD")
+	} else {
+		random::derive(object_key, &field.name)
+	};
+
+	if let DataTypes::Optional(chance, _) = &field.datatype {
+		if happens(field_seed, *chance) {
+			return None;
+		}
+	}
+
+	Some(build_value(schemas, &field.datatype, &Context{ id: ctx.id.to_owned(), params: ctx.params.clone(), seed: field_seed, root: ctx.root, size: ctx.size, settings: ctx.settings }))
+}
+
+fn uses_field_variable(datatype: &DataTypes) -> bool {
+	match datatype {
+		DataTypes::String(expressions) => expressions.iter().any(|expression| matches!(expression, StringExpressions::Variable(s) if s.starts_with("FIELD."))),
+		DataTypes::Optional(_, datatype) | DataTypes::Nullable(_, datatype) => uses_field_variable(datatype),
+		_ => false,
+	}
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Dates {
 	Future,
@@ -191,25 +278,432 @@ pub enum StringExpressions {
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum NumberExpressions {
-	Literal(i64),
+	Literal(serde_json::Number),
 	Range(i64, i64),
+	/// Decimal numbers between a minimum and a maximum, both inclusive.
+	/// The bounds are in units of the last decimal, e.g. `Decimal(50, 9990, 2)` is from 0.5 to 99.9.
+	Decimal(i64, i64, u32),
 	Variable(String),
 }
 
+/// Picks one of the values with a chance proportional to its weight, or returns `None` if every weight is 0.
+fn pick_weighted<T>(values: &[(T, u32)], key: usize) -> Option<&T> {
+	let total = values.iter().map(|(_, weight)| *weight as usize).sum::<usize>();
+
+	if total == 0 {
+		return None;
+	}
+
+	let mut key = key % total;
+
+	values.iter().find(|(_, weight)| {
+		if key < *weight as usize {
+			true
+		} else {
+			key -= *weight as usize;
+			false
+		}
+	}).map(|(value, _)| value)
+}
+
+/// What a chance is out of, so a chance of 1 is one in a million.
+pub const CHANCE_SCALE: usize = 1_000_000;
+
+fn happens(key: usize, chance: u32) -> bool {
+	key % CHANCE_SCALE < chance as usize
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BooleanExpressions {
+	Literal(bool),
+	/// True with this chance, out of `CHANCE_SCALE`.
+	Chance(u32),
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum ObjectExpressions {
 	Object(Vec<Field>),
 	Schema(String),
 }
 
+/// How many items an array is generated with.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Lengths {
+	/// As many items as the scale factor.
+	Scale,
+	/// Exactly this many items.
+	Count(usize),
+	/// Between a minimum and a maximum number of items, both inclusive.
+	/// A missing minimum is 0 and a missing maximum is the scale factor.
+	Range(Option<usize>, Option<usize>),
+}
+
+impl Lengths {
+	pub fn resolve(&self, size: usize, key: usize) -> usize {
+		match self {
+			Lengths::Scale => size,
+			Lengths::Count(count) => *count,
+			Lengths::Range(min, max) => {
+				let min = min.unwrap_or(0);
+				let max = max.unwrap_or(size).max(min);
+
+				match (max - min).checked_add(1) {
+					Some(span) => min + key % span,
+					// Every length is in the range
+					None => key,
+				}
+			},
+		}
+	}
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum DataTypes {
 	String(Vec<StringExpressions>),
 	Number(NumberExpressions),
 	Object(ObjectExpressions),
-	Array(ObjectExpressions),
-	Enum(Vec<String>),
+	/// Items of any type, each generated with its index as the id.
+	Array(Box<DataTypes>, Lengths),
+	/// Values of any JSON type, picked with a chance proportional to their weight.
+	Enum(Vec<(serde_json::Value, u32)>),
+	/// An object of a schema, or one of its fields, picked from the items its collection route serves.
+	Reference(String, Option<String>),
+	/// A fixed list of values, like a literal array.
+	List(Vec<DataTypes>),
+	Boolean(BooleanExpressions),
+	/// A field left out of its object with this chance, out of `CHANCE_SCALE`.
+	Optional(u32, Box<DataTypes>),
+	/// A value that is null with this chance, out of `CHANCE_SCALE`.
+	Nullable(u32, Box<DataTypes>),
 	Null,
 }
 
-// TODO: test
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_resolve_lengths() {
+		assert_eq!(Lengths::Scale.resolve(16, 7), 16);
+		assert_eq!(Lengths::Count(3).resolve(16, 7), 3);
+
+		for key in 0..64 {
+			let length = Lengths::Range(Some(2), Some(5)).resolve(16, key);
+			assert!((2..=5).contains(&length));
+			assert_eq!(length, Lengths::Range(Some(2), Some(5)).resolve(16, key));
+
+			assert!(Lengths::Range(None, Some(3)).resolve(16, key) <= 3);
+			assert!((10..=16).contains(&Lengths::Range(Some(10), None).resolve(16, key)));
+			assert_eq!(Lengths::Range(Some(20), None).resolve(16, key), 20);
+		}
+
+		assert!((0..64).map(|key| Lengths::Range(Some(2), Some(5)).resolve(16, key)).any(|length| length == 5));
+		assert_eq!(Lengths::Range(None, Some(usize::MAX)).resolve(16, 7), 7);
+		assert!(Lengths::Range(Some(usize::MAX - 1), Some(usize::MAX)).resolve(16, 7) >= usize::MAX - 1);
+	}
+
+	#[test]
+	fn test_pick() {
+		let picks = (0..64).map(|key| pick(18, 20, key)).collect::<Vec<_>>();
+		assert!(picks.iter().all(|value| (18..=20).contains(value)));
+		assert!(picks.contains(&18) && picks.contains(&20));
+
+		assert_eq!(pick(5, 5, 12345), 5);
+		assert_eq!(pick(9, 1, 12345), 9);
+		assert!((-3..=3).contains(&pick(-3, 3, usize::MAX)));
+		assert_eq!(pick(i64::MIN, i64::MAX, 0), i64::MIN);
+
+		let schemas = HashMap::new();
+		let settings = Settings::default();
+		let ctx = Context{ id: None, params: vec![], seed: 7, root: 7, size: 16, settings: &settings };
+		assert_eq!(build_value(&schemas, &DataTypes::Number(NumberExpressions::Range(4, 4)), &ctx), serde_json::json!(4));
+		assert_eq!(build_value(&schemas, &DataTypes::String(vec![StringExpressions::Range(4, 4)]), &ctx), serde_json::json!("4"));
+	}
+
+	#[test]
+	fn test_build_array_length() {
+		let schemas = HashMap::new();
+		let item = Box::new(DataTypes::Object(ObjectExpressions::Object(vec![])));
+
+		let settings = Settings::default();
+		let value = build_value(&schemas, &DataTypes::Array(item.clone(), Lengths::Scale), &Context{ id: None, params: vec![], seed: 0, root: 0, size: 4, settings: &settings });
+		assert_eq!(value.as_array().unwrap().len(), 4);
+
+		let value = build_value(&schemas, &DataTypes::Array(item.clone(), Lengths::Count(7)), &Context{ id: None, params: vec![], seed: 0, root: 0, size: 4, settings: &settings });
+		assert_eq!(value.as_array().unwrap().len(), 7);
+	}
+
+	#[test]
+	fn test_build_literals() {
+		let schemas = HashMap::new();
+		let literals = DataTypes::Object(ObjectExpressions::Object(vec![
+			Field { name: "name".to_string(), datatype: DataTypes::String(vec![StringExpressions::Literal("John Doe".to_string())]) },
+			Field { name: "age".to_string(), datatype: DataTypes::Number(NumberExpressions::Literal(25.into())) },
+			Field { name: "score".to_string(), datatype: DataTypes::Number(NumberExpressions::Literal(serde_json::Number::from_f64(0.5).unwrap())) },
+			Field { name: "active".to_string(), datatype: DataTypes::Boolean(BooleanExpressions::Literal(true)) },
+			Field { name: "manager".to_string(), datatype: DataTypes::Null },
+			Field { name: "tags".to_string(), datatype: DataTypes::List(vec![DataTypes::String(vec![StringExpressions::Literal("a".to_string())]), DataTypes::Boolean(BooleanExpressions::Literal(false))]) },
+		]));
+
+		let settings = Settings::default();
+
+		for seed in 0..4 {
+			let value = build_value(&schemas, &literals, &Context{ id: Some(seed.to_string()), params: vec![], seed, root: seed, size: 16, settings: &settings });
+			assert_eq!(value, serde_json::json!({ "name": "John Doe", "age": 25, "score": 0.5, "active": true, "manager": null, "tags": ["a", false] }));
+		}
+	}
+
+	#[test]
+	fn test_build_generated_numbers_and_booleans() {
+		let schemas = HashMap::new();
+		let settings = Settings::default();
+		let ctx = |seed: usize| Context{ id: None, params: vec![], seed, root: seed, size: 16, settings: &settings };
+
+		let prices = (0..1000).map(|seed| build_value(&schemas, &DataTypes::Number(NumberExpressions::Decimal(50, 9990, 2)), &ctx(seed)).as_f64().unwrap()).collect::<Vec<_>>();
+		assert!(prices.iter().all(|price| (0.5..=99.9).contains(price) && (price * 100.0).round() / 100.0 == *price));
+		assert!(prices.iter().any(|price| price.fract() != 0.0));
+		assert_eq!(build_value(&schemas, &DataTypes::Number(NumberExpressions::Decimal(50, 9990, 2)), &ctx(7)), build_value(&schemas, &DataTypes::Number(NumberExpressions::Decimal(50, 9990, 2)), &ctx(7)));
+		assert_eq!(build_value(&schemas, &DataTypes::Number(NumberExpressions::Decimal(3, 3, 0)), &ctx(7)), serde_json::json!(3));
+
+		let count = |chance: u32| (0..1000).filter(|id: &usize| build_value(&schemas, &DataTypes::Boolean(BooleanExpressions::Chance(chance)), &Context{ id: Some(id.to_string()), params: vec![], seed: 0, root: 0, size: 16, settings: &settings }) == true).count();
+		assert_eq!(count(0), 0);
+		assert_eq!(count(CHANCE_SCALE as u32), 1000);
+		assert!((200..400).contains(&count(300_000)));
+	}
+
+	#[test]
+	fn test_build_optional_and_nullable() {
+		let schemas = HashMap::new();
+		let name = DataTypes::String(vec![StringExpressions::Literal("John Doe".to_string())]);
+		let person = DataTypes::Object(ObjectExpressions::Object(vec![
+			Field { name: "name".to_string(), datatype: DataTypes::Optional(300_000, Box::new(name.clone())) },
+			Field { name: "nickname".to_string(), datatype: DataTypes::Nullable(100_000, Box::new(name.clone())) },
+			Field { name: "email".to_string(), datatype: DataTypes::Optional(CHANCE_SCALE as u32, Box::new(name.clone())) },
+			Field { name: "phone".to_string(), datatype: DataTypes::Nullable(0, Box::new(name.clone())) },
+		]));
+
+		let settings = Settings::default();
+		let people = (0..1000).map(|id| build_value(&schemas, &person, &Context{ id: Some(id.to_string()), params: vec![], seed: 0, root: 0, size: 16, settings: &settings })).collect::<Vec<_>>();
+
+		let missing = people.iter().filter(|person| person.get("name").is_none()).count();
+		assert!((200..400).contains(&missing));
+
+		let null = people.iter().filter(|person| person["nickname"].is_null()).count();
+		assert!((50..150).contains(&null));
+
+		assert!(people.iter().all(|person| person.get("email").is_none() && person["phone"] == "John Doe"));
+
+		// The same id always has the same shape
+		assert_eq!(build_value(&schemas, &person, &Context{ id: Some("7".to_string()), params: vec![], seed: 0, root: 0, size: 16, settings: &settings }), people[7]);
+	}
+
+	#[test]
+	fn test_build_primitive_arrays() {
+		let schemas = HashMap::new();
+		let settings = Settings::default();
+		let ctx = Context{ id: Some("3".to_string()), params: vec![], seed: 0, root: 0, size: 16, settings: &settings };
+
+		let tags = build_value(&schemas, &DataTypes::Array(Box::new(DataTypes::Enum(vec![("red".into(), 1), ("green".into(), 1), ("blue".into(), 1)])), Lengths::Count(32)), &ctx);
+		let tags = tags.as_array().unwrap();
+		assert_eq!(tags.len(), 32);
+		assert!(tags.iter().all(|tag| ["red", "green", "blue"].contains(&tag.as_str().unwrap())));
+		assert!(tags.iter().any(|tag| *tag != tags[0]));
+
+		let scores = build_value(&schemas, &DataTypes::Array(Box::new(DataTypes::Number(NumberExpressions::Range(0, 10))), Lengths::Count(4)), &ctx);
+		assert!(scores.as_array().unwrap().iter().all(|score| (0..=10).contains(&score.as_i64().unwrap())));
+
+		let matrix = build_value(&schemas, &DataTypes::Array(Box::new(DataTypes::Array(Box::new(DataTypes::Boolean(BooleanExpressions::Literal(true))), Lengths::Count(2))), Lengths::Count(3)), &ctx);
+		assert_eq!(matrix, serde_json::json!([[true, true], [true, true], [true, true]]));
+	}
+
+	#[test]
+	fn test_route_params() {
+		let schemas = HashMap::new();
+		let person = DataTypes::Object(ObjectExpressions::Object(vec![
+			Field { name: "org".to_string(), datatype: DataTypes::String(vec![StringExpressions::Variable("params.orgId".to_string())]) },
+			Field { name: "id".to_string(), datatype: DataTypes::String(vec![StringExpressions::Variable("this.id".to_string())]) },
+			Field { name: "name".to_string(), datatype: DataTypes::String(vec![StringExpressions::Variable("FULL_NAME".to_string())]) },
+		]));
+		let people = DataTypes::Array(Box::new(DataTypes::Object(ObjectExpressions::Object(vec![
+			Field { name: "name".to_string(), datatype: DataTypes::String(vec![StringExpressions::Variable("FULL_NAME".to_string())]) },
+		]))), Lengths::Count(4));
+
+		let params = |org: &str, person: Option<&str>| {
+			let mut params = vec![("orgId".to_string(), org.to_string())];
+			params.extend(person.map(|person| ("personId".to_string(), person.to_string())));
+			params
+		};
+
+		let settings = Settings::default();
+		let value = build_value(&schemas, &person, &Context::from_params(params("acme", Some("2")), 0, 16, &settings));
+		assert_eq!(value["org"], "acme");
+		assert_eq!(value["id"], "2");
+
+		// A nested item is generated the same way as the matching item of its parent collection
+		let list = build_value(&schemas, &people, &Context::from_params(params("acme", None), 0, 16, &settings));
+		assert_eq!(list[2]["name"], value["name"]);
+
+		// And so is a top-level one, and one nested twice
+		let list = build_value(&schemas, &people, &Context::from_params(vec![], 0, 16, &settings));
+		let item = build_value(&schemas, &person, &Context::from_params(vec![("id".to_string(), "3".to_string())], 0, 16, &settings));
+		assert_eq!(list[3]["name"], item["name"]);
+
+		let mut nested = params("acme", Some("2"));
+		let list = build_value(&schemas, &people, &Context::from_params(nested.clone(), 0, 16, &settings));
+		nested.push(("postId".to_string(), "1".to_string()));
+		let item = build_value(&schemas, &person, &Context::from_params(nested, 0, 16, &settings));
+		assert_eq!(list[1]["name"], item["name"]);
+	}
+
+	#[test]
+	fn test_build_reference() {
+		let mut schemas = HashMap::new();
+		schemas.insert("Person".to_string(), Schema {
+			name: "Person".to_string(),
+			fields: vec![
+				Field { name: "id".to_string(), datatype: DataTypes::String(vec![StringExpressions::Variable("this.id::UUID".to_string())]) },
+				Field { name: "name".to_string(), datatype: DataTypes::String(vec![StringExpressions::Variable("FULL_NAME".to_string())]) },
+			],
+			collection: Some(Lengths::Count(5)),
+		});
+
+		let settings = Settings::default();
+		let ctx = Context::from_params(vec![], 3, 16, &settings);
+
+		let people = build_value(&schemas, &DataTypes::Array(Box::new(DataTypes::Object(ObjectExpressions::Schema("Person".to_string()))), Lengths::Count(5)), &ctx);
+
+		for id in 0..32 {
+			let ctx = Context::from_params(vec![("id".to_string(), id.to_string())], 3, 16, &settings);
+
+			let author_id = build_value(&schemas, &DataTypes::Reference("Person".to_string(), Some("id".to_string())), &ctx);
+			assert!(people.as_array().unwrap().iter().any(|person| person["id"] == author_id));
+
+			let author = build_value(&schemas, &DataTypes::Reference("Person".to_string(), None), &ctx);
+			assert!(people.as_array().unwrap().contains(&author));
+		}
+	}
+
+	#[test]
+	fn test_build_self_reference() {
+		let mut schemas = HashMap::new();
+		schemas.insert("Person".to_string(), Schema {
+			name: "Person".to_string(),
+			fields: vec![
+				Field { name: "id".to_string(), datatype: DataTypes::String(vec![StringExpressions::Variable("this.id::UUID".to_string())]) },
+				Field { name: "managerId".to_string(), datatype: DataTypes::Reference("Person".to_string(), Some("id".to_string())) },
+			],
+			collection: Some(Lengths::Count(8)),
+		});
+
+		let settings = Settings::default();
+		let people = build_value(&schemas, &DataTypes::Array(Box::new(DataTypes::Object(ObjectExpressions::Schema("Person".to_string()))), Lengths::Count(8)), &Context::from_params(vec![], 3, 16, &settings));
+		let people = people.as_array().unwrap();
+
+		for person in people {
+			assert!(people.iter().any(|manager| manager["id"] == person["managerId"]));
+		}
+	}
+
+	#[test]
+	fn test_build_weighted_enum() {
+		let schemas = HashMap::new();
+		let settings = Settings::default();
+
+		let status = DataTypes::Enum(vec![("active".into(), 80), ("pending".into(), 18), ("banned".into(), 2), ("deleted".into(), 0)]);
+		let statuses = (0..10_000).map(|id| build_value(&schemas, &status, &Context{ id: Some(id.to_string()), params: vec![], seed: 0, root: 0, size: 16, settings: &settings })).collect::<Vec<_>>();
+
+		let count = |value: &str| statuses.iter().filter(|status| *status == value).count();
+		assert!((7_700..8_300).contains(&count("active")), "{}", count("active"));
+		assert!((1_600..2_000).contains(&count("pending")), "{}", count("pending"));
+		assert!((120..280).contains(&count("banned")), "{}", count("banned"));
+		assert_eq!(count("deleted"), 0);
+
+		assert_eq!(pick_weighted(&[("a", 1), ("b", 2)], 0), Some(&"a"));
+		assert_eq!(pick_weighted(&[("a", 1), ("b", 2)], 2), Some(&"b"));
+		assert_eq!(pick_weighted(&[("a", 0)], 7), None);
+
+		// Values keep their JSON type
+		let plans = [serde_json::json!(1), serde_json::json!(true), serde_json::json!({ "name": "Free", "price": 0 })];
+		let plan = DataTypes::Enum(plans.iter().map(|plan| (plan.clone(), 1)).collect());
+		let built = (0..64).map(|id| build_value(&schemas, &plan, &Context{ id: Some(id.to_string()), params: vec![], seed: 0, root: 0, size: 16, settings: &settings })).collect::<Vec<_>>();
+		assert!(plans.iter().all(|plan| built.contains(plan)));
+		assert!(built.iter().all(|value| plans.contains(value)));
+	}
+
+	#[test]
+	fn test_build_independent_fields() {
+		let schemas = HashMap::new();
+
+		let letters = DataTypes::Enum(('a'..='l').map(|letter| (letter.to_string().into(), 1)).collect());
+		let pair = DataTypes::Object(ObjectExpressions::Object(vec![
+			Field { name: "first".to_string(), datatype: letters.clone() },
+			Field { name: "second".to_string(), datatype: letters.clone() },
+			Field { name: "nested".to_string(), datatype: DataTypes::Object(ObjectExpressions::Object(vec![Field { name: "first".to_string(), datatype: letters }])) },
+		]));
+
+		let settings = Settings::default();
+		let pairs = (0..1200).map(|id| build_value(&schemas, &pair, &Context::from_params(vec![("id".to_string(), id.to_string())], 0, 16, &settings))).collect::<Vec<_>>();
+
+		// Fields with the same generator agree about as often as chance would have it, 1 in 12
+		let same = pairs.iter().filter(|pair| pair["first"] == pair["second"]).count();
+		assert!((50..150).contains(&same), "{}", same);
+
+		let same = pairs.iter().filter(|pair| pair["first"] == pair["nested"]["first"]).count();
+		assert!((50..150).contains(&same), "{}", same);
+	}
+
+	#[test]
+	fn test_build_field_pairs() {
+		let schemas = HashMap::new();
+
+		let variable = |name: &str| DataTypes::String(vec![StringExpressions::Variable(name.to_string())]);
+		let pair = DataTypes::Object(ObjectExpressions::Object(vec![
+			Field { name: "name".to_string(), datatype: variable("FIELD.name") },
+			Field { name: "value".to_string(), datatype: DataTypes::Optional(0, Box::new(variable("FIELD.value"))) },
+		]));
+
+		let settings = Settings::default();
+		let country = fake::get_fake_variable("COUNTRY", 0, settings.locale).unwrap();
+		let pairs = (0..240).map(|id| build_value(&schemas, &pair, &Context::from_params(vec![("id".to_string(), id.to_string())], 0, 16, &settings))).collect::<Vec<_>>();
+
+		// The value is always generated for the field the name is
+		for name in ["age", "email", "country"] {
+			let values = pairs.iter().filter(|pair| pair["name"] == name).map(|pair| pair["value"].as_str().unwrap()).collect::<Vec<_>>();
+			assert!(!values.is_empty(), "{}", name);
+
+			assert!(values.iter().all(|value| match name {
+				"age" => value.parse::<u32>().is_ok_and(|age| (18..=80).contains(&age)),
+				"email" => value.contains('@'),
+				_ => *value == country,
+			}), "{}: {:?}", name, values);
+		}
+	}
+
+	#[test]
+	fn test_golden_output() {
+		// Generated values must only change when the configuration or the seed does
+		let mut schemas = HashMap::new();
+		schemas.insert("Person".to_string(), Schema {
+			name: "Person".to_string(),
+			fields: vec![
+				Field { name: "name".to_string(), datatype: DataTypes::String(vec![StringExpressions::Variable("FULL_NAME".to_string())]) },
+				Field { name: "age".to_string(), datatype: DataTypes::Number(NumberExpressions::Range(18, 90)) },
+				Field { name: "balance".to_string(), datatype: DataTypes::Number(NumberExpressions::Decimal(0, 100_000, 2)) },
+				Field { name: "active".to_string(), datatype: DataTypes::Boolean(BooleanExpressions::Chance(500_000)) },
+				Field { name: "role".to_string(), datatype: DataTypes::Enum(vec![("admin".into(), 1), ("editor".into(), 1), ("viewer".into(), 1)]) },
+			],
+			collection: None,
+		});
+
+		let person = DataTypes::Object(ObjectExpressions::Schema("Person".to_string()));
+
+		let settings = Settings::default();
+		let values = (0..3).map(|id| build_value(&schemas, &person, &Context::from_params(vec![("id".to_string(), id.to_string())], 0, 16, &settings))).collect::<Vec<_>>();
+
+		assert_eq!(serde_json::Value::Array(values), serde_json::json!([
+			{ "name": "Brandon Gonzalez", "age": 73, "balance": 878.21, "active": true, "role": "editor" },
+			{ "name": "Diana Warren", "age": 71, "balance": 172.31, "active": true, "role": "editor" },
+			{ "name": "Christine Grant", "age": 77, "balance": 152.15, "active": true, "role": "admin" },
+		]));
+	}
+}